@@ -1,10 +1,27 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDate, Utc};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use colored::*;
-use reqwest::blocking::Client;
-use serde::Deserialize;
-use std::collections::HashMap;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, log_enabled, Level};
+use pkgtime::{
+    cargo_sparse_releases, check_max_age, eligible_versions, fetch_pip_hashes, fetch_pip_releases,
+    fetch_pip_requires, fetch_releases, github_repo, nearby_versions, normalize_name,
+    npm_latest_at, parse_pip_spec, pip_anchor_window, select_champion, split_constraint,
+    versions_overlapping_window, Constraint, DiskCache, Fetcher, Manager, PackageVersion, Pick,
+    ResolveError, ResolveOptions, SelectBy,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
@@ -16,24 +33,395 @@ struct Args {
     #[arg(value_enum)]
     manager: Option<Manager>,
 
-    /// The cutoff date (YYYY-MM-DD)
+    /// The cutoff date (YYYY-MM-DD, today, yesterday, an offset like 30d, 6mo, 1y, or unix seconds)
     date: Option<String>,
 
-    /// List of packages to check
+    /// List of packages to check, each optionally constrained as
+    /// `name@>=2,<3` (`-` reads them from stdin, one per line)
     packages: Vec<String>,
 
-    /// Verbose output
-    #[arg(short, long, global = true)]
-    verbose: bool,
+    /// Take each package as `manager:name` (e.g. `pip:requests npm:react`)
+    /// instead of resolving them all with MANAGER
+    #[arg(long)]
+    mixed: bool,
+
+    /// Read packages from a manifest (requirements.txt for pip, package.json,
+    /// pnpm-lock.yaml or yarn.lock for npm, Cargo.toml (with any workspace
+    /// members) for cargo, Gemfile.lock or Gemfile for gem)
+    #[arg(long, value_name = "PATH")]
+    from_file: Option<PathBuf>,
+
+    /// Skip pre-release versions (alpha, beta, rc, dev, ...)
+    #[arg(long)]
+    stable_only: bool,
+
+    /// Add --no-deps to the pip install line, so dependencies aren't
+    /// upgraded past the cutoff
+    #[arg(long)]
+    pip_no_deps: bool,
+
+    /// Print pip pins as a hash-checked requirements file, with the sha256 of
+    /// every file of each release (one extra request per package)
+    #[arg(long)]
+    pip_hashes: bool,
+
+    /// When a pip package isn't found, suggest the closest name from PyPI's
+    /// full project index (a download of tens of MB, cached like any other)
+    #[arg(long)]
+    pip_suggest: bool,
+
+    /// Like --stable-only, but accept a pre-release when no stable version
+    /// was released before the cutoff
+    #[arg(long)]
+    prerelease_fallback: bool,
+
+    /// npm: take the version the `latest` dist-tag pointed to at the cutoff,
+    /// skipping the scan of every release when the package hasn't changed
+    /// since
+    #[arg(long, conflicts_with = "list")]
+    batch_npm: bool,
+
+    /// Cargo: enumerate versions from the sparse index and look up dates only
+    /// for the ones near the cutoff, sparing the rate-limited crates.io API
+    /// the full version listing
+    #[arg(long, conflicts_with = "list")]
+    cargo_sparse: bool,
+
+    /// Consider versions that were yanked from the registry
+    #[arg(long)]
+    include_yanked: bool,
+
+    /// RubyGems platform to resolve builds for, e.g. java or x86_64-linux
+    #[arg(long, value_name = "PLATFORM", default_value = "ruby")]
+    gem_platform: String,
+
+    /// Also resolve the dependencies of every pip package at the same date,
+    /// and theirs, for a complete pinned set
+    #[arg(long)]
+    recursive: bool,
+
+    /// Consider Composer branch versions (dev-master, 2.x-dev, ...)
+    #[arg(long)]
+    include_dev: bool,
+
+    /// Only accept a release published on DATE itself, failing otherwise
+    #[arg(long)]
+    require_on_date: bool,
+
+    /// Pick the newest release by publish date, the highest version number,
+    /// or the newest release of the highest major version
+    #[arg(long, visible_alias = "strategy", value_enum, default_value_t = SelectBy::Date)]
+    select_by: SelectBy,
+
+    /// Conda channel for packages not given as `channel::name`
+    #[arg(long, value_name = "CHANNEL", default_value = pkgtime::DEFAULT_CONDA_CHANNEL)]
+    conda_channel: String,
+
+    /// Use the HEAD commit date of the git repository at PATH as the cutoff
+    /// (DATE is then left out)
+    #[arg(long, value_name = "PATH")]
+    as_of_git: Option<PathBuf>,
+
+    /// Resolve at each of these comma-separated cutoffs instead of DATE (which
+    /// is then left out) and print a package × date table
+    #[arg(
+        long,
+        value_name = "DATES",
+        value_delimiter = ',',
+        conflicts_with_all = ["as_of_git", "list", "lockfile", "report", "batch_npm", "cargo_sparse", "quiet"]
+    )]
+    dates: Vec<String>,
+
+    /// Start of the date window: ignore releases published before this day
+    #[arg(long, value_name = "DATE")]
+    min_date: Option<String>,
+
+    /// strftime format for DATE and --min-date, e.g. %d/%m/%Y
+    #[arg(long, value_name = "FORMAT", default_value = DEFAULT_DATE_FORMAT)]
+    date_format: String,
+
+    /// Return the latest or the earliest eligible release
+    #[arg(long, value_enum, default_value_t = Pick::Latest)]
+    pick: Pick,
+
+    /// Also print the N releases immediately before and after the cutoff
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    show_nearby: usize,
+
+    /// Choose each package's pin from a numbered menu of the releases around
+    /// the cutoff (--show-nearby N per side, 3 by default)
+    #[arg(long, conflicts_with_all = ["dates", "list"])]
+    interactive: bool,
+
+    /// Warn when the resolved release is more than this many days older
+    /// than the cutoff (0 disables the warning)
+    #[arg(long, value_name = "DAYS", default_value_t = 365)]
+    stale_warn_days: u64,
+
+    /// Don't warn when the cutoff date is in the future
+    #[arg(long)]
+    allow_future: bool,
+
+    /// Print each resolved package as this line instead, with {name},
+    /// {version}, {date}, {manager} and {days_before} substituted
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "list")]
+    template: Option<String>,
+
+    /// Print each package that failed as this line instead, with {name},
+    /// {manager} and {error} substituted
+    #[arg(long, value_name = "TEMPLATE")]
+    error_template: Option<String>,
+
+    /// Count a package as an error when its pick is more than this many days
+    /// older than the cutoff
+    #[arg(long, value_name = "DAYS")]
+    max_age_days: Option<u64>,
+
+    /// Also write the resolved pins to PATH in the manager's manifest format
+    /// (requirements.txt, package.json, Cargo.toml, Gemfile, composer.json, ...)
+    #[arg(long, value_name = "PATH")]
+    lockfile: Option<PathBuf>,
+
+    /// Also write a JSON provenance report to PATH: tool version, cutoff,
+    /// run time and every package's outcome, sorted by manager and name
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Order in which result lines are printed
+    #[arg(long, value_enum, default_value_t = SortOrder::Input)]
+    sort: SortOrder,
+
+    /// List every eligible release before the cutoff instead of picking one
+    #[arg(long)]
+    list: bool,
+
+    /// Print resolution counts instead of install instructions
+    #[arg(long)]
+    summary: bool,
+
+    /// Put install instructions and errors under a header per manager,
+    /// for mixed-manager runs
+    #[arg(long)]
+    group_output: bool,
+
+    /// Resolve repeated packages (e.g. `Django django`) every time they appear
+    #[arg(long)]
+    no_dedupe: bool,
+
+    /// Only resolve the first N packages (after dropping repeats), e.g. for a
+    /// quick check against a long requirements file
+    #[arg(long, value_name = "N")]
+    count: Option<usize>,
+
+    /// Stop at the first package that fails to resolve
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Number of packages to resolve concurrently
+    #[arg(short, long, default_value_t = 8)]
+    jobs: usize,
+
+    /// At most this many requests to any one registry host at a time
+    #[arg(long, value_name = "N", default_value_t = pkgtime::DEFAULT_PER_HOST_JOBS)]
+    per_host_jobs: usize,
+
+    /// Always query the registries, bypassing the on-disk response cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Read registry responses from DIR/<manager>/<package>.json instead of
+    /// the network
+    #[arg(long, global = true, value_name = "DIR")]
+    offline: Option<PathBuf>,
+
+    /// How long (in seconds) cached registry responses stay fresh
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = 3600)]
+    cache_ttl: u64,
+
+    /// How long (in seconds) a "not found" answer is cached, so typos don't
+    /// hit the registry on every run
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = 300)]
+    cache_negative_ttl: u64,
+
+    /// HTTP request timeout in seconds (120 for apt unless given)
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = 30)]
+    timeout: u64,
+
+    /// Whether `timeout` came from the command line or config file
+    #[arg(skip)]
+    timeout_given: bool,
+
+    /// Retries for transient registry failures (429, 5xx, connection errors)
+    #[arg(long, global = true, value_name = "N", default_value_t = pkgtime::DEFAULT_RETRIES)]
+    retries: u32,
+
+    /// Bearer token for registries given a custom URL (e.g. a private GitLab
+    /// package registry); never sent to the public registries
+    #[arg(
+        long,
+        global = true,
+        value_name = "TOKEN",
+        env = "PIPTIME_TOKEN",
+        hide_env_values = true
+    )]
+    auth_token: Option<String>,
+
+    /// Proxy for all registry requests (defaults to HTTP_PROXY/HTTPS_PROXY;
+    /// hosts in NO_PROXY bypass it either way)
+    #[arg(long, global = true, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Don't verify TLS certificates (for mirrors with self-signed ones;
+    /// prefer --ca-cert)
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// Also trust the root certificate in this PEM or DER file
+    #[arg(long, global = true, value_name = "PATH")]
+    ca_cert: Vec<PathBuf>,
+
+    /// Fall back to this registry mirror when the registry errors or times
+    /// out; repeat to try several in order. Prefix with MANAGER= to choose
+    /// the registry it mirrors (needed with --mirror in --mixed runs)
+    #[arg(long, global = true, value_name = "URL")]
+    mirror: Vec<String>,
+
+    /// User-Agent header to send instead of pkgtime/<version> (+<repo URL>)
+    #[arg(long, global = true, value_name = "STRING")]
+    user_agent: Option<String>,
+
+    /// Base URL of the PyPI mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_PYPI_URL")]
+    pypi_url: Option<String>,
+
+    /// Base URL of the npm registry (falls back to NPM_CONFIG_REGISTRY)
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        env = "PIPTIME_NPM_URL",
+        alias = "npm-registry"
+    )]
+    npm_url: Option<String>,
+
+    /// Base URL of the crates.io mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_CRATES_URL")]
+    crates_url: Option<String>,
+
+    /// Base URL of the sparse index --cargo-sparse reads (required with
+    /// --crates-url, which the public index doesn't describe)
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        env = "PIPTIME_CARGO_INDEX_URL"
+    )]
+    cargo_index_url: Option<String>,
+
+    /// Base URL of the RubyGems mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_RUBYGEMS_URL")]
+    rubygems_url: Option<String>,
+
+    /// Base URL of the Packagist mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_PACKAGIST_URL")]
+    packagist_url: Option<String>,
+
+    /// Base URL of the Go module proxy mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_GOPROXY_URL")]
+    goproxy_url: Option<String>,
+
+    /// Base URL of the Maven Central search mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_MAVEN_URL")]
+    maven_url: Option<String>,
+
+    /// Base URL of the NuGet mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_NUGET_URL")]
+    nuget_url: Option<String>,
+
+    /// Base URL of the pub.dev mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_PUB_URL")]
+    pub_url: Option<String>,
+
+    /// Base URL of the hex.pm mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_HEX_URL")]
+    hex_url: Option<String>,
+
+    /// Base URL of the crandb mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_CRAN_URL")]
+    cran_url: Option<String>,
+
+    /// Base URL of the anaconda.org API mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_ANACONDA_URL")]
+    anaconda_url: Option<String>,
+
+    /// Base URL of the CocoaPods trunk mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_COCOAPODS_URL")]
+    cocoapods_url: Option<String>,
+
+    /// Base URL of the MetaCPAN API mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_METACPAN_URL")]
+    metacpan_url: Option<String>,
+
+    /// Base URL of the snapshot.debian.org mirror to query
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        env = "PIPTIME_DEBIAN_SNAPSHOT_URL"
+    )]
+    debian_snapshot_url: Option<String>,
+
+    /// Base URL of the Hackage mirror to query
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_HACKAGE_URL")]
+    hackage_url: Option<String>,
+
+    /// Base URL of the GitHub API (used for Homebrew, Swift and GitHub releases)
+    #[arg(long, global = true, value_name = "URL", env = "PIPTIME_GITHUB_URL")]
+    github_url: Option<String>,
+
+    /// When to use colored output
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Verbose output: log requests, rejected versions and timings to stderr
+    /// (shorthand for RUST_LOG=pkgtime=debug); twice also logs HTTP headers
+    /// and cache hits, with credentials masked (RUST_LOG=pkgtime=trace)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Print only the install instructions; errors go to stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
+    /// Resolve packages at a cutoff date; the same as leaving the subcommand
+    /// out: pkgtime resolve <MANAGER> <DATE> <PACKAGES>...
+    Resolve,
+    /// Print every release of a package with its publish date
+    List(ListArgs),
+    /// Print the supported managers and the registry each one queries
+    Managers,
     /// Find versions that were "latest" during an anchor package-version window
     /// (from the anchor release time until the next release time).
     Overlap(OverlapArgs),
 }
 
+#[derive(Parser, Debug)]
+struct ListArgs {
+    /// The package manager to use
+    #[arg(value_enum)]
+    manager: Manager,
+
+    /// The package whose releases to print
+    package: String,
+}
+
 #[derive(Parser, Debug)]
 struct OverlapArgs {
     /// The package manager to use
@@ -48,645 +436,3070 @@ struct OverlapArgs {
     packages: Vec<String>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
-enum Manager {
-    Pip,
-    Npm,
-    Cargo,
-    Gem,
-    Composer,
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// One JSON object per line, printed as each package resolves
+    Jsonl,
+    /// A single uncolored `RUN` line installing every pin
+    Dockerfile,
 }
 
-struct PackageVersion {
-    version: String,
-    date: DateTime<Utc>,
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum SortOrder {
+    /// As given on the command line or in the manifest
+    Input,
+    Name,
+    /// Oldest resolved release first; unresolved packages last
+    Date,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Defaults read from `piptime.toml`. Anything given on the command line (or
+/// through an environment variable) takes precedence.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    manager: Option<Manager>,
+    jobs: Option<usize>,
+    timeout: Option<u64>,
+    stable_only: Option<bool>,
+    /// Registry base URLs keyed by manager, e.g. `pip = "https://..."`
+    registries: HashMap<Manager, String>,
+    /// Sparse index for `--cargo-sparse`, alongside `registries.cargo`
+    cargo_index_url: Option<String>,
+}
+
+/// A package's release history together with the version picked from it.
+struct Lookup {
+    chosen: Option<PackageVersion>,
+    /// The releases `chosen` was picked from, oldest first
+    candidates: Vec<PackageVersion>,
+    releases: Vec<PackageVersion>,
+}
+
+/// One element of the `--output json` array.
+#[derive(Serialize)]
+struct JsonResult<'a> {
+    package: &'a str,
+    manager: Manager,
+    resolved_version: Option<String>,
+    resolved_date: Option<DateTime<Utc>>,
+    error: Option<String>,
+    /// Every eligible release, with `--list`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    versions: Option<Vec<JsonVersion>>,
 }
 
-struct WindowedVersion {
+/// The `--report` document.
+#[derive(Serialize)]
+struct Report<'a> {
+    tool: &'static str,
+    version: &'static str,
+    cutoff: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+    packages: Vec<JsonResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonVersion {
     version: String,
-    overlap_start: DateTime<Utc>,
-    overlap_end: DateTime<Utc>,
+    date: DateTime<Utc>,
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     // Enable color support on Windows
     #[cfg(windows)]
     let _ = colored::control::set_virtual_terminal(true);
 
-    let args = Args::parse();
+    let config = load_config()?;
+    let args = parse_args(&config);
+    configure_color(args.color);
+    init_logging(args.verbose);
+
+    let uses_apt = if args.mixed {
+        let apt = |p: &String| split_mixed(p).is_ok_and(|(m, _)| m == Manager::Apt);
+        args.packages.iter().any(apt)
+    } else {
+        args.manager == Some(Manager::Apt)
+    };
+    let timeout = if uses_apt && !args.timeout_given {
+        pkgtime::APT_TIMEOUT
+    } else {
+        Duration::from_secs(args.timeout)
+    };
+    let mut fetcher = Fetcher::new(timeout)?;
+    if let Some(proxy) = &args.proxy {
+        fetcher
+            .set_proxy(proxy)
+            .unwrap_or_else(|e| usage_error(format!("--proxy: {:#}", e)));
+    }
+    for path in &args.ca_cert {
+        fetcher
+            .add_ca_cert(path)
+            .unwrap_or_else(|e| usage_error(format!("--ca-cert: {:#}", e)));
+    }
+    if args.insecure {
+        eprintln!(
+            "{}",
+            "⚠️  --insecure: TLS certificates are not verified, so registry responses can be tampered with"
+                .red()
+                .bold()
+        );
+        fetcher.set_insecure()?;
+    }
+    fetcher.gem_platform = args.gem_platform.clone();
+    if let Some(user_agent) = &args.user_agent {
+        fetcher
+            .set_user_agent(user_agent)
+            .unwrap_or_else(|e| usage_error(format!("--user-agent: {:#}", e)));
+    }
+    if !args.no_cache {
+        fetcher.cache = DiskCache::new(
+            Duration::from_secs(args.cache_ttl),
+            Duration::from_secs(args.cache_negative_ttl),
+        );
+    }
+    fetcher.offline = args.offline.clone();
+    fetcher.retries = args.retries;
+    fetcher.per_host_jobs = args.per_host_jobs;
+    fetcher.pip_suggestions = args.pip_suggest;
+    fetcher.registry_urls = registry_overrides(&args, &config);
+    fetcher.cargo_index_url = args
+        .cargo_index_url
+        .clone()
+        .or_else(|| config.cargo_index_url.clone())
+        .filter(|u| !u.is_empty());
+    if args.cargo_sparse
+        && fetcher.registry_urls.contains_key(&Manager::Cargo)
+        && fetcher.cargo_index_url.is_none()
+    {
+        usage_error(
+            "--cargo-sparse with a custom crates registry needs its sparse index too (--cargo-index-url)",
+        );
+    }
+    let mirrored = match &args.command {
+        Some(Command::List(l)) => Some(l.manager),
+        _ => args.manager,
+    };
+    fetcher.mirrors = parse_mirrors(&args.mirror, mirrored)
+        .unwrap_or_else(|e| usage_error(format!("--mirror: {}", e)));
+    fetcher.github_token = std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty());
+    fetcher.auth_token = args.auth_token.clone().filter(|t| !t.is_empty());
 
     if let Some(command) = args.command {
         return match command {
-            Command::Overlap(o) => run_overlap(o, args.verbose),
+            Command::Overlap(o) => run_overlap(o, &fetcher).map(|()| ExitCode::SUCCESS),
+            Command::List(l) => run_list(l, &fetcher).map(|()| ExitCode::SUCCESS),
+            Command::Managers => {
+                print_managers(&fetcher);
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Resolve => unreachable!("parse_args drops the resolve subcommand"),
         };
     }
 
-    let manager = args
-        .manager
-        .context("Missing MANAGER argument (or use a subcommand)")?;
-    let date = args
-        .date
-        .context("Missing DATE argument (or use a subcommand)")?;
-
-    if args.packages.is_empty() {
-        return Err(anyhow::anyhow!(
-            "Missing PACKAGES argument(s) (or use a subcommand)"
-        ));
+    // `None` under --mixed, where every package names its own manager
+    let manager = match args.manager {
+        _ if args.mixed => None,
+        Some(manager) => Some(manager),
+        None => usage_error("Missing MANAGER argument (or use a subcommand)"),
+    };
+    if args.mixed && (args.from_file.is_some() || args.lockfile.is_some()) {
+        usage_error("--mixed can't be combined with --from-file or --lockfile");
+    }
+    for (flag, template, fields) in [
+        ("--template", &args.template, &TEMPLATE_FIELDS[..]),
+        (
+            "--error-template",
+            &args.error_template,
+            &ERROR_TEMPLATE_FIELDS[..],
+        ),
+    ] {
+        if let Some(key) = template
+            .as_deref()
+            .and_then(|t| unknown_placeholder(t, fields))
+        {
+            usage_error(format!(
+                "{}: unknown placeholder {{{}}} (expected one of {{{}}})",
+                flag,
+                key,
+                fields.join("}, {")
+            ));
+        }
+    }
+    if args.recursive && (manager != Some(Manager::Pip) || !args.dates.is_empty()) {
+        usage_error("--recursive only works for pip, and not with --dates");
+    }
+    let mut packages = args.packages;
+    // With --as-of-git or --dates there is no DATE, so clap took the first
+    // package for it
+    let no_date = args.as_of_git.is_some() || !args.dates.is_empty();
+    let date = match (no_date, args.date) {
+        (true, Some(first)) => {
+            packages.insert(0, first);
+            None
+        }
+        (true, None) => None,
+        (false, Some(date)) => Some(date),
+        (false, None) => usage_error("Missing DATE argument (or use a subcommand)"),
+    };
+    if packages == ["-"] && args.interactive {
+        usage_error("--interactive reads answers from stdin, so packages can't come from it");
+    }
+    if packages == ["-"] {
+        let content = std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?;
+        packages = parse_package_list(&content);
+    }
+    if let (Some(path), Some(manager)) = (&args.from_file, manager) {
+        packages.extend(read_packages_file(manager, path)?);
     }
 
-    // Parse date
-    let naive_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
-        .context("Invalid date format. Use YYYY-MM-DD")?;
-    // Set time to end of day to include releases on that day
-    let target_date = naive_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
+    if packages.is_empty() {
+        usage_error("Missing PACKAGES argument(s) (or use --from-file or a subcommand)");
+    }
 
-    println!(
-        "--- Searching for {} packages up to {} ---",
-        format!("{:?}", manager).yellow(),
-        target_date.date_naive().to_string().yellow()
-    );
+    let mut names = Vec::with_capacity(packages.len());
+    let mut managers = Vec::with_capacity(packages.len());
+    let mut constraints = Vec::with_capacity(packages.len());
+    let mut seen = HashSet::new();
+    for pkg in &packages {
+        let (pkg_manager, spec) = match manager {
+            Some(manager) => (manager, pkg.as_str()),
+            None => split_mixed(pkg).unwrap_or_else(|e| usage_error(e)),
+        };
+        let (name, constraint) =
+            split_constraint(pkg_manager, spec).unwrap_or_else(|e| usage_error(format!("{:#}", e)));
+        let name = if pkg_manager == Manager::Conda && !name.contains("::") {
+            format!("{}::{}", args.conda_channel, name)
+        } else {
+            name.to_string()
+        };
+        if !args.no_dedupe && !seen.insert((pkg_manager, normalize_name(pkg_manager, &name))) {
+            debug!("Skipping duplicate {}", name);
+            continue;
+        }
+        names.push(name);
+        managers.push(pkg_manager);
+        constraints.push(constraint);
+    }
+    if let Some(count) = args.count {
+        names.truncate(count);
+        managers.truncate(count);
+        constraints.truncate(count);
+    }
+    let mut packages = names;
 
-    let client = Client::builder()
-        .user_agent("pkgtime/1.0 (pkgtime-tool)")
-        .build()?;
+    let end_of_day = |input: &str, what: &str| {
+        if let Some(instant) = parse_timestamp(input, &args.date_format) {
+            return instant
+                .unwrap_or_else(|| usage_error(format!("{}timestamp out of range", what)));
+        }
+        let day = parse_cutoff_date(input, Utc::now().date_naive(), &args.date_format)
+            .unwrap_or_else(|e| usage_error(format!("{}{}", what, e)));
+        // Set time to end of day to include releases on that day
+        day.and_hms_opt(23, 59, 59).unwrap().and_utc()
+    };
+    let cutoffs: Vec<DateTime<Utc>> = args
+        .dates
+        .iter()
+        .map(|d| end_of_day(d, "--dates: "))
+        .collect();
+    if !cutoffs.is_empty() && args.output != OutputFormat::Text {
+        usage_error("--dates only supports text output");
+    }
+    if args.output == OutputFormat::Dockerfile && (args.list || args.pip_hashes) {
+        usage_error("--output dockerfile can't be combined with --list or --pip-hashes");
+    }
+    let target_date = match (&args.as_of_git, date) {
+        (Some(repo), _) => git_head_date(repo)?,
+        (None, Some(date)) => end_of_day(&date, ""),
+        // Histories are fetched once; the matrix then picks at every cutoff
+        (None, None) => *cutoffs.iter().max().expect("DATE or --dates is required"),
+    };
+    let naive_date = target_date.date_naive();
+    let today = Utc::now().date_naive();
+    if naive_date > today && !args.allow_future {
+        // Usually a typo'd year; everything would silently resolve to latest
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  The cutoff {} is in the future, so every result is just the latest \
+                 release available today (pass --allow-future if that's intended)",
+                naive_date
+            )
+            .yellow()
+            .bold()
+        );
+    }
+    let min_date = args.min_date.as_deref().map(|d| {
+        let day = parse_cutoff_date(d, Utc::now().date_naive(), &args.date_format)
+            .unwrap_or_else(|e| usage_error(format!("--min-date: {}", e)));
+        if day > naive_date {
+            usage_error("--min-date must not be after DATE");
+        }
+        day.and_hms_opt(0, 0, 0).unwrap().and_utc()
+    });
+
+    if args.output == OutputFormat::Text && !args.quiet {
+        println!(
+            "--- Searching for {} packages up to {} ---",
+            manager
+                .map_or("mixed".to_string(), |m| format!("{:?}", m))
+                .yellow(),
+            match cutoffs.as_slice() {
+                [] => target_date.date_naive().to_string().yellow(),
+                _ => cutoffs
+                    .iter()
+                    .map(|c| c.date_naive().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .yellow(),
+            }
+        );
+    }
 
-    let mut install_cmds = Vec::new();
     let mut errors = Vec::new();
 
-    for pkg in &args.packages {
-        match find_version(&client, manager, pkg, target_date, args.verbose) {
-            Ok(Some(v)) => {
-                println!(
-                    "✅ {}: {} (from {})",
-                    pkg.green(),
-                    v.version.bold(),
-                    v.date.date_naive()
-                );
-
-                let cmd = match manager {
-                    Manager::Pip => format!("{}=={}", pkg, v.version),
-                    Manager::Npm => format!("{}@{}", pkg, v.version),
-                    Manager::Cargo => format!("{} = \"={}\"", pkg, v.version),
-                    Manager::Gem => format!("gem '{}', '{}'", pkg, v.version),
-                    Manager::Composer => format!("{}:{}", pkg, v.version),
-                };
-                install_cmds.push(cmd);
-            }
-            Ok(None) => {
-                let msg = "No version found before the specified date";
-                println!("❌ {}: {}", pkg.red(), msg);
-                errors.push(format!("{}: {}", pkg, msg));
-            }
-            Err(e) => {
-                println!("❌ {}: {}", pkg.red(), e);
-                errors.push(format!("{}: {}", pkg, e));
-            }
+    let opts = ResolveOptions {
+        stable_only: args.stable_only,
+        include_yanked: args.include_yanked,
+        require_on_date: args.require_on_date,
+        select_by: args.select_by,
+        min_date,
+        pick: args.pick,
+        include_dev: args.include_dev,
+        prerelease_fallback: args.prerelease_fallback,
+        npm_latest: args.batch_npm,
+        cargo_sparse: args.cargo_sparse,
+        max_age_days: args.max_age_days,
+    };
+    // Results are printed once everything is resolved, so the bar never
+    // shares the terminal with them; log output would interleave though
+    let progress = if args.output == OutputFormat::Text
+        && !args.quiet
+        && !log_enabled!(Level::Debug)
+        && std::io::stdout().is_terminal()
+    {
+        ProgressBar::new(packages.len() as u64).with_style(
+            ProgressStyle::with_template("resolved {pos} / {len} packages {wide_bar}")
+                .expect("valid progress template"),
+        )
+    } else {
+        ProgressBar::hidden()
+    };
+    let started = Instant::now();
+    let run = |managers: &[Manager], packages: &[String], constraints: &[Option<Constraint>]| {
+        resolve_all(
+            &fetcher,
+            managers,
+            packages,
+            constraints,
+            target_date,
+            &opts,
+            args.jobs,
+            args.fail_fast,
+            &|idx, result| {
+                progress.inc(1);
+                if args.output == OutputFormat::Jsonl {
+                    print_json_line(&json_record(
+                        managers[idx],
+                        &packages[idx],
+                        result,
+                        constraints[idx].as_ref(),
+                        target_date,
+                        args.list,
+                    ));
+                }
+            },
+        )
+    };
+    let mut results = run(&managers, &packages, &constraints);
+    if args.recursive {
+        // Each round resolves the dependencies first seen in the previous
+        // one; `seen` is what stops cycles
+        let mut seen: HashSet<String> = packages
+            .iter()
+            .map(|p| normalize_name(Manager::Pip, p))
+            .collect();
+        let mut round_start = 0;
+        while round_start < results.len() && !(args.fail_fast && results.iter().any(is_failure)) {
+            let (deps, dep_constraints) = pip_dependencies(
+                &fetcher,
+                &packages[round_start..],
+                &mut results[round_start..],
+                &mut seen,
+            );
+            round_start = results.len();
+            progress.inc_length(deps.len() as u64);
+            let dep_managers = vec![Manager::Pip; deps.len()];
+            results.extend(run(&dep_managers, &deps, &dep_constraints));
+            packages.extend(deps);
+            managers.extend(dep_managers);
+            constraints.extend(dep_constraints);
         }
     }
+    progress.finish_and_clear();
+    let elapsed = started.elapsed();
+    if !cutoffs.is_empty() {
+        let complete = print_matrix(
+            &packages,
+            &managers,
+            &constraints,
+            &results,
+            &cutoffs,
+            &opts,
+        );
+        return Ok(ExitCode::from(match exit_status(&results) {
+            0 if !complete => 1,
+            status => status,
+        }));
+    }
 
-    println!("{}", "-".repeat(60));
+    let any_failed = results.iter().any(is_failure);
+    let stopped_early = args.fail_fast && any_failed;
+    let exit_code = ExitCode::from(exit_status(&results));
 
-    if !install_cmds.is_empty() {
-        print_install_instructions(manager, &install_cmds);
+    if args.interactive && !stopped_early {
+        let per_side = if args.show_nearby > 0 {
+            args.show_nearby
+        } else {
+            3
+        };
+        let mut input = std::io::stdin().lock();
+        for (pkg, result) in packages.iter().zip(&mut results) {
+            if let Ok(lookup) = result {
+                choose_interactively(pkg, lookup, target_date, per_side, &mut input)?;
+            }
+        }
     }
 
-    if !errors.is_empty() {
-        println!("\n{}", "Attention to errors:".yellow());
-        for err in errors {
-            println!(" - {}", err);
+    // cpanm and cpanfiles take a module, not the distribution name asked for
+    let install_names: Vec<String> = packages
+        .iter()
+        .zip(&managers)
+        .zip(&results)
+        .map(|((pkg, &manager), result)| {
+            let chosen = result.as_ref().ok().and_then(|l| l.chosen.as_ref());
+            match (manager, chosen) {
+                (Manager::Cpan, Some(v)) => fetcher
+                    .cpan_main_module(pkg, &v.version)
+                    .unwrap_or_else(|| pkg.replace('-', "::")),
+                _ => pkg.clone(),
+            }
+        })
+        .collect();
+    let pins: Vec<(Manager, &str, &str)> = install_names
+        .iter()
+        .zip(&managers)
+        .zip(&results)
+        .filter_map(|((pkg, &manager), result)| {
+            let v = result.as_ref().ok()?.chosen.as_ref()?;
+            Some((manager, pkg.as_str(), v.version.as_str()))
+        })
+        .collect();
+
+    let mut pip_hashes: HashMap<&str, Vec<String>> = HashMap::new();
+    if args.pip_hashes && !stopped_early {
+        for &(_, pkg, version) in pins.iter().filter(|(m, ..)| *m == Manager::Pip) {
+            let hashes = fetch_pip_hashes(&fetcher, pkg, version)
+                .with_context(|| format!("Failed to fetch hashes for {}=={}", pkg, version))?;
+            pip_hashes.insert(pkg, hashes);
+        }
+    }
+    let install_cmd = |manager: Manager, pkg: &str, version: &str| match pip_hashes.get(pkg) {
+        Some(hashes) if manager == Manager::Pip => hashed_requirement(pkg, version, hashes),
+        _ => install_cmd(manager, pkg, version),
+    };
+
+    // --mixed rules out --lockfile, so there is a single manager here
+    if let (Some(path), Some(manager)) = (args.lockfile.as_ref(), manager) {
+        if !stopped_early {
+            let content = if manager == Manager::Pip && args.pip_hashes {
+                let lines: Vec<String> = pins
+                    .iter()
+                    .map(|&(_, pkg, v)| install_cmd(manager, pkg, v) + "\n")
+                    .collect();
+                lines.concat()
+            } else {
+                let pins: Vec<(&str, &str)> = pins.iter().map(|&(_, pkg, v)| (pkg, v)).collect();
+                render_lockfile(manager, &pins)
+            };
+            std::fs::write(path, content)
+                .with_context(|| format!("Failed to write lockfile '{}'", path.display()))?;
         }
     }
 
-    Ok(())
-}
-
-fn run_overlap(args: OverlapArgs, verbose: bool) -> Result<()> {
-    if args.manager != Manager::Pip {
-        return Err(anyhow::anyhow!(
-            "The overlap command is currently only supported for 'pip'"
-        ));
+    if let Some(path) = &args.report {
+        let report = render_report(
+            &managers,
+            &packages,
+            &constraints,
+            &results,
+            target_date,
+            Utc::now(),
+        )?;
+        std::fs::write(path, report)
+            .with_context(|| format!("Failed to write report '{}'", path.display()))?;
     }
 
-    let (anchor_pkg, anchor_ver) = parse_pip_spec(&args.anchor)?;
+    // Grouped by manager, in the order each first appears
+    let mut install_cmds: Vec<(Manager, Vec<String>)> = Vec::new();
+    for &(manager, pkg, version) in &pins {
+        let cmd = install_cmd(manager, pkg, version);
+        match install_cmds.iter_mut().find(|(m, _)| *m == manager) {
+            Some((_, cmds)) => cmds.push(cmd),
+            None => install_cmds.push((manager, vec![cmd])),
+        }
+    }
 
-    let client = Client::builder()
-        .user_agent("pkgtime/1.0 (pkgtime-tool)")
-        .build()?;
+    if args.output == OutputFormat::Jsonl {
+        return Ok(exit_code);
+    }
+    if args.output == OutputFormat::Json {
+        print_json_results(
+            &managers,
+            &packages,
+            &constraints,
+            results,
+            target_date,
+            args.list,
+        )?;
+        return Ok(exit_code);
+    }
+    if args.output == OutputFormat::Dockerfile {
+        for ((pkg, result), constraint) in packages.iter().zip(&results).zip(&constraints) {
+            match result {
+                Ok(lookup @ Lookup { chosen: None, .. }) => {
+                    eprintln!(
+                        "error: {}: {}",
+                        pkg,
+                        no_version_message(constraint.as_ref(), &lookup.releases, target_date)
+                    )
+                }
+                Err(e) => eprintln!("error: {}: {}", pkg, e),
+                Ok(_) => {}
+            }
+        }
+        if !stopped_early && !pins.is_empty() {
+            let line = dockerfile_run(&pins, args.pip_no_deps)
+                .unwrap_or_else(|e| usage_error(format!("--output dockerfile: {}", e)));
+            println!("{}", line);
+        }
+        return Ok(exit_code);
+    }
 
-    let anchor_releases = fetch_pip_releases(&client, &anchor_pkg, verbose).with_context(|| {
-        format!(
-            "Failed to fetch releases for anchor package '{}'",
-            anchor_pkg
-        )
-    })?;
+    if args.quiet {
+        for ((pkg, result), constraint) in packages.iter().zip(&results).zip(&constraints) {
+            match result {
+                Ok(Lookup {
+                    chosen: Some(_), ..
+                }) => {}
+                Ok(lookup) => eprintln!(
+                    "error: {}: {}",
+                    pkg,
+                    no_version_message(constraint.as_ref(), &lookup.releases, target_date)
+                ),
+                Err(e) if e.is_lookup_failure() => {
+                    eprintln!(
+                        "error: {}: lookup failed (network/registry error): {}",
+                        pkg, e
+                    )
+                }
+                Err(e) => eprintln!("error: {}: {}", pkg, e),
+            }
+        }
+        if stopped_early {
+            eprintln!("Stopped at the first failure (--fail-fast)");
+        } else {
+            for (manager, cmds) in &install_cmds {
+                print_manager_instructions(*manager, cmds, args.pip_no_deps);
+            }
+        }
+        return Ok(exit_code);
+    }
 
-    let (window_start, window_end) = pip_anchor_window(&anchor_pkg, &anchor_ver, &anchor_releases)?;
+    for idx in result_order(&packages, &results, args.sort) {
+        let (pkg, result) = (&packages[idx], &results[idx]);
+        if let Some(name) = fetcher.canonical_name(managers[idx], pkg) {
+            println!(
+                "{}",
+                format!("ℹ️  resolved '{}' as '{}'", pkg, name).dimmed()
+            );
+        }
+        match result {
+            Ok(lookup) if args.list => print_version_list(pkg, lookup),
+            Ok(lookup) => {
+                match &lookup.chosen {
+                    Some(v) => {
+                        let gap = cutoff_gap((target_date - v.date).num_days());
+                        let gap = match stale_days(v.date, target_date, args.stale_warn_days) {
+                            Some(_) => format!("⚠ {}", gap).yellow().to_string(),
+                            None => gap,
+                        };
+                        let platform = match managers[idx] {
+                            Manager::Gem if fetcher.gem_platform != "ruby" => {
+                                format!(" [{}]", fetcher.gem_platform)
+                            }
+                            _ => String::new(),
+                        };
+                        match &args.template {
+                            Some(template) => println!(
+                                "{}",
+                                render_template(
+                                    template,
+                                    &[
+                                        ("name", pkg),
+                                        ("version", &v.version),
+                                        ("date", &v.date.date_naive().to_string()),
+                                        ("manager", &manager_name(managers[idx])),
+                                        (
+                                            "days_before",
+                                            &(target_date - v.date).num_days().to_string()
+                                        ),
+                                    ]
+                                )
+                            ),
+                            None => println!(
+                                "✅ {}: {}{} (from {}, {})",
+                                pkg.green(),
+                                v.version.bold(),
+                                platform,
+                                v.date.date_naive(),
+                                gap
+                            ),
+                        }
+                    }
+                    None => {
+                        let msg = no_version_message(
+                            constraints[idx].as_ref(),
+                            &lookup.releases,
+                            target_date,
+                        );
+                        match &args.error_template {
+                            Some(template) => {
+                                println!("{}", error_line(template, managers[idx], pkg, &msg))
+                            }
+                            None => println!("❌ {}: {}", pkg.red(), msg),
+                        }
+                        errors.push((managers[idx], format!("{}: {}", pkg, msg)));
+                    }
+                }
+                if args.show_nearby > 0 {
+                    print_nearby(lookup, target_date, args.show_nearby);
+                }
+            }
+            Err(e) if e.is_lookup_failure() => {
+                match &args.error_template {
+                    Some(template) => {
+                        let msg = format!("lookup failed (network/registry error): {}", e);
+                        println!("{}", error_line(template, managers[idx], pkg, &msg))
+                    }
+                    None => println!(
+                        "⚠️  {}: {}: {}",
+                        pkg.yellow(),
+                        "lookup failed (network/registry error)".yellow(),
+                        e
+                    ),
+                }
+                errors.push((managers[idx], format!("{}: lookup failed: {}", pkg, e)));
+            }
+            Err(e) => {
+                match &args.error_template {
+                    Some(template) => {
+                        println!(
+                            "{}",
+                            error_line(template, managers[idx], pkg, &e.to_string())
+                        )
+                    }
+                    None => println!("❌ {}: {}", pkg.red(), e),
+                }
+                errors.push((managers[idx], format!("{}: {}", pkg, e)));
+            }
+        }
+    }
 
     println!(
-        "--- Overlap window for {} ---",
-        format!("{}=={}", anchor_pkg, anchor_ver).yellow()
-    );
-    println!(
-        "Window: {} -> {}",
-        window_start.to_rfc3339().yellow(),
-        window_end.to_rfc3339().yellow()
+        "{}",
+        format!("Checked {} packages in {:.2?}", results.len(), elapsed).dimmed()
     );
     println!("{}", "-".repeat(60));
 
-    let mut errors = Vec::new();
-
-    for pkg in &args.packages {
-        match fetch_pip_releases(&client, pkg, verbose) {
-            Ok(releases) => {
-                let overlaps = versions_overlapping_window(&releases, window_start, window_end);
-                if overlaps.is_empty() {
-                    println!(
-                        "{}: {}",
-                        pkg.yellow(),
-                        "no overlapping latest versions".dimmed()
-                    );
-                    continue;
-                }
+    if stopped_early {
+        println!("{}", "Stopped at the first failure (--fail-fast)".yellow());
+    } else if args.list {
+        // The listing is the result; there is no single pin to install
+    } else if args.summary {
+        print_summary(&results);
+    } else if !install_cmds.is_empty() {
+        print_install_instructions(&install_cmds, args.group_output, args.pip_no_deps);
+    }
 
-                let parts: Vec<String> = overlaps
-                    .into_iter()
-                    .map(|o| {
-                        format!(
-                            "{} ({}..{})",
-                            o.version.bold(),
-                            o.overlap_start.date_naive(),
-                            o.overlap_end.date_naive()
-                        )
-                    })
-                    .collect();
+    if let Some(path) = args.lockfile.as_ref().filter(|_| !stopped_early) {
+        println!(
+            "Lockfile written to {}",
+            path.display().to_string().yellow()
+        );
+    }
+    if let Some(path) = &args.report {
+        println!("Report written to {}", path.display().to_string().yellow());
+    }
 
-                println!("{}: {}", pkg.green(), parts.join(", "));
+    if !errors.is_empty() {
+        println!("\n{}", "Attention to errors:".yellow());
+        if args.group_output {
+            let mut grouped: Vec<(Manager, Vec<String>)> = Vec::new();
+            for (manager, err) in errors {
+                match grouped.iter_mut().find(|(m, _)| *m == manager) {
+                    Some((_, errs)) => errs.push(err),
+                    None => grouped.push((manager, vec![err])),
+                }
             }
-            Err(e) => {
-                println!("❌ {}: {}", pkg.red(), e);
-                errors.push(format!("{}: {}", pkg, e));
+            for (manager, errs) in grouped {
+                println!("{}", manager_header(manager));
+                for err in errs {
+                    println!(" - {}", err);
+                }
+            }
+        } else {
+            for (_, err) in errors {
+                println!(" - {}", err);
             }
         }
     }
 
-    if !errors.is_empty() {
-        println!("\n{}", "Attention to errors:".yellow());
-        for err in errors {
-            println!(" - {}", err);
+    Ok(exit_code)
+}
+
+/// `--list` output: every eligible release of `pkg`, oldest first.
+fn print_version_list(pkg: &str, lookup: &Lookup) {
+    println!(
+        "📜 {}: {} eligible releases",
+        pkg.green(),
+        lookup.candidates.len()
+    );
+    let width = lookup
+        .candidates
+        .iter()
+        .map(|v| v.version.len())
+        .max()
+        .unwrap_or(0);
+    for v in &lookup.candidates {
+        let line = format!("{:width$}  {}", v.version, v.date.date_naive());
+        if lookup
+            .chosen
+            .as_ref()
+            .is_some_and(|c| c.version == v.version)
+        {
+            println!("   {} {}", line.bold(), "<- picked".dimmed());
+        } else {
+            println!("   {}", line);
         }
     }
+}
 
-    Ok(())
+/// Indices into `results` in the order `--sort` asks for. The sorts are
+/// stable, so ties keep their input order.
+fn result_order(
+    packages: &[String],
+    results: &[Result<Lookup, ResolveError>],
+    sort: SortOrder,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..results.len()).collect();
+    match sort {
+        SortOrder::Input => {}
+        SortOrder::Name => order.sort_by_key(|&i| packages[i].to_lowercase()),
+        SortOrder::Date => order.sort_by_key(|&i| {
+            let date = results[i]
+                .as_ref()
+                .ok()
+                .and_then(|l| l.chosen.as_ref())
+                .map(|v| v.date);
+            (date.is_none(), date)
+        }),
+    }
+    order
 }
 
-fn print_install_instructions(manager: Manager, cmds: &[String]) {
-    println!("Copy and paste into your configuration:");
-    println!();
-    match manager {
-        Manager::Pip => println!("{}pip install {}{}", "\x1b[92m", cmds.join(" "), "\x1b[0m"),
-        Manager::Npm => println!("{}npm install {}{}", "\x1b[92m", cmds.join(" "), "\x1b[0m"),
-        Manager::Cargo => {
-            println!("{}# Cargo.toml dependencies:{}", "\x1b[92m", "\x1b[0m");
-            for cmd in cmds {
-                println!("{}{}{}", "\x1b[92m", cmd, "\x1b[0m");
+/// Reports a bad invocation the way clap does, exiting with status 2.
+fn usage_error(msg: impl std::fmt::Display) -> ! {
+    Args::command()
+        .error(ErrorKind::ValueValidation, msg)
+        .exit()
+}
+
+/// Placeholders `--template` accepts.
+const TEMPLATE_FIELDS: [&str; 5] = ["name", "version", "date", "manager", "days_before"];
+/// Placeholders `--error-template` accepts.
+const ERROR_TEMPLATE_FIELDS: [&str; 3] = ["name", "manager", "error"];
+
+/// Substitutes the `{key}` placeholders found in `values`; any other text,
+/// braces included, is copied as written.
+fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let (_, value) = values.iter().find(|(key, _)| *key == &after[..end])?;
+            Some((end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
             }
-        }
-        Manager::Gem => {
-            println!("{}# Gemfile:{}", "\x1b[92m", "\x1b[0m");
-            for cmd in cmds {
-                println!("{}{}{}", "\x1b[92m", cmd, "\x1b[0m");
+            None => {
+                out.push('{');
+                rest = after;
             }
         }
-        Manager::Composer => {
-            println!(
-                "{}composer require {}{}",
-                "\x1b[92m",
-                cmds.join(" "),
-                "\x1b[0m"
-            );
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The first `{word}` in `template` that isn't one of `fields`, so typos are
+/// reported up front instead of printed verbatim.
+fn unknown_placeholder<'a>(template: &'a str, fields: &[&str]) -> Option<&'a str> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let key = &rest[..end];
+        let is_word = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_word && !fields.contains(&key) {
+            return Some(key);
         }
     }
-    println!();
+    None
 }
 
-fn find_version(
-    client: &Client,
-    manager: Manager,
-    pkg: &str,
+fn error_line(template: &str, manager: Manager, pkg: &str, error: &str) -> String {
+    render_template(
+        template,
+        &[
+            ("name", pkg),
+            ("manager", &manager_name(manager)),
+            ("error", error),
+        ],
+    )
+}
+
+/// The manager as spelled on the command line, e.g. `pip`.
+fn manager_name(manager: Manager) -> String {
+    manager
+        .to_possible_value()
+        .map_or_else(String::new, |v| v.get_name().to_string())
+}
+
+/// Why a package resolved to nothing, naming the constraint that may be to
+/// blame.
+fn no_version_message(
+    constraint: Option<&Constraint>,
+    releases: &[PackageVersion],
     target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    match manager {
-        Manager::Pip => find_pip(client, pkg, target_date, verbose),
-        Manager::Npm => find_npm(client, pkg, target_date, verbose),
-        Manager::Cargo => find_cargo(client, pkg, target_date, verbose),
-        Manager::Gem => find_gem(client, pkg, target_date, verbose),
-        Manager::Composer => find_composer(client, pkg, target_date, verbose),
+) -> String {
+    // Sorted by date, so the first release is the earliest
+    if let Some(first) = releases.first().filter(|v| v.date > target_date) {
+        return format!(
+            "Package exists, but its first release ({}) was on {}, after the cutoff",
+            first.version,
+            first.date.date_naive()
+        );
+    }
+    match constraint {
+        Some(c) => match c.baseline() {
+            Some(current) => format!(
+                "No version newer than {} before the specified date",
+                current
+            ),
+            None => format!("No version matching '{}' before the specified date", c),
+        },
+        None => "No version found before the specified date".to_string(),
     }
 }
 
-// --- PIP Strategy ---
-#[derive(Deserialize)]
-struct PipReleaseFile {
-    #[serde(default)]
-    upload_time: Option<String>,
-    #[serde(default)]
-    upload_time_iso_8601: Option<String>,
+/// "14 days before cutoff", for the gap between a release and the cutoff.
+fn cutoff_gap(days: i64) -> String {
+    match days {
+        0 => "on the cutoff day".to_string(),
+        1 => "1 day before cutoff".to_string(),
+        n => format!("{} days before cutoff", n),
+    }
 }
-#[derive(Deserialize)]
-struct PipData {
-    releases: HashMap<String, Vec<PipReleaseFile>>,
+
+/// How long before `target_date` a resolved release was published, if that
+/// exceeds `threshold` days; a large gap usually means the package went
+/// quiet and the pin may be older than intended.
+fn stale_days(date: DateTime<Utc>, target_date: DateTime<Utc>, threshold: u64) -> Option<i64> {
+    let days = (target_date - date).num_days();
+    (threshold > 0 && days > threshold as i64).then_some(days)
 }
 
-fn parse_pip_spec(spec: &str) -> Result<(String, String)> {
-    let (name, version) = spec
-        .split_once("==")
-        .context("Anchor must be in format <package>==<version>")?;
+/// Splits a `--mixed` entry such as `pip:requests` into its manager and the
+/// rest of the spec (`conda:bioconda::samtools` keeps its channel).
+fn split_mixed(entry: &str) -> Result<(Manager, &str), String> {
+    let (prefix, spec) = entry
+        .split_once(':')
+        .filter(|(_, spec)| !spec.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "'{}' needs a manager prefix with --mixed, e.g. pip:{}",
+                entry, entry
+            )
+        })?;
+    let manager = Manager::from_str(prefix, true)
+        .map_err(|_| format!("Unknown manager '{}' in '{}'", prefix, entry))?;
+    Ok((manager, spec))
+}
 
-    if name.trim().is_empty() || version.trim().is_empty() {
-        return Err(anyhow::anyhow!(
-            "Anchor must be in format <package>==<version>"
-        ));
+/// `0` when everything resolved, `3` when any registry couldn't be queried
+/// (so a retry may help), otherwise `1` for packages with no match.
+fn exit_status(results: &[Result<Lookup, ResolveError>]) -> u8 {
+    if results
+        .iter()
+        .any(|r| r.as_ref().is_err_and(ResolveError::is_lookup_failure))
+    {
+        3
+    } else if results.iter().any(is_failure) {
+        1
+    } else {
+        0
     }
+}
 
-    Ok((name.trim().to_string(), version.trim().to_string()))
+fn is_failure(result: &Result<Lookup, ResolveError>) -> bool {
+    !matches!(
+        result,
+        Ok(Lookup {
+            chosen: Some(_),
+            ..
+        })
+    )
 }
 
-fn pip_file_upload_time(file: &PipReleaseFile) -> Option<DateTime<Utc>> {
-    if let Some(ts) = &file.upload_time_iso_8601 {
-        if let Ok(date) = DateTime::parse_from_rfc3339(ts) {
-            return Some(date.with_timezone(&Utc));
+const CONFIG_FILE: &str = "piptime.toml";
+
+/// Reads the first `piptime.toml` found in the current directory or the
+/// user's config directory.
+fn load_config() -> Result<Config> {
+    let candidates = [
+        Some(PathBuf::from(CONFIG_FILE)),
+        dirs::config_dir().map(|d| d.join(CONFIG_FILE)),
+    ];
+    for path in candidates.into_iter().flatten() {
+        if !path.is_file() {
+            continue;
         }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read '{}'", path.display()))?;
+        return toml::from_str(&content)
+            .with_context(|| format!("Failed to parse '{}'", path.display()));
     }
+    Ok(Config::default())
+}
 
-    if let Some(ts) = &file.upload_time {
-        // "2019-05-16T17:21:44" (typically UTC but without timezone info)
-        let layout = "%Y-%m-%dT%H:%M:%S";
-        if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(ts, layout) {
-            return Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
-        }
+/// Parses the command line, filling in anything left at its built-in default
+/// from `config`.
+fn parse_args(config: &Config) -> Args {
+    let mut argv: Vec<String> = std::env::args().collect();
+    // `resolve` only spells out the default, so the flat form's arguments
+    // (and config handling) apply unchanged
+    if argv.get(1).is_some_and(|a| a == "resolve") {
+        argv.remove(1);
     }
-
-    None
+    let matches = match Args::command().try_get_matches_from(&argv) {
+        Ok(matches) => matches,
+        // With a configured manager (or under --mixed, where it's unused) the
+        // MANAGER positional may be omitted, in which case clap sees the date
+        // in its place.
+        Err(e)
+            if e.kind() == ErrorKind::InvalidValue
+                && (config.manager.is_some() || argv.iter().any(|a| a == "--mixed")) =>
+        {
+            let Some(ContextValue::String(value)) = e.get(ContextKind::InvalidValue) else {
+                e.exit()
+            };
+            let Some(pos) = argv.iter().skip(1).position(|a| a == value) else {
+                e.exit()
+            };
+            let manager = config.manager.unwrap_or(Manager::Pip);
+            let name = manager.to_possible_value().unwrap();
+            argv.insert(pos + 1, name.get_name().to_string());
+            Args::command().get_matches_from(&argv)
+        }
+        Err(e) => e.exit(),
+    };
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    apply_config(&mut args, config, &matches);
+    args
 }
 
-fn fetch_pip_releases(client: &Client, pkg: &str, verbose: bool) -> Result<Vec<PackageVersion>> {
-    let url = format!("https://pypi.org/pypi/{}/json", pkg);
-    if verbose {
-        println!(" -> Fetching {}", url);
+fn apply_config(args: &mut Args, config: &Config, matches: &ArgMatches) {
+    let unset = |id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    if args.manager.is_none() {
+        args.manager = config.manager;
     }
-
-    let resp = client.get(&url).send()?;
-    if resp.status() == 404 {
-        return Err(anyhow::anyhow!("Package not found on PyPI"));
+    if let Some(jobs) = config.jobs.filter(|_| unset("jobs")) {
+        args.jobs = jobs;
     }
-    let data: PipData = resp.json()?;
-
-    let mut releases = Vec::new();
-
-    for (version, files) in data.releases {
-        let first_upload = files
-            .into_iter()
-            .filter_map(|f| pip_file_upload_time(&f))
-            .min();
-        if let Some(date) = first_upload {
-            releases.push(PackageVersion { version, date });
-        }
+    if let Some(timeout) = config.timeout.filter(|_| unset("timeout")) {
+        args.timeout = timeout;
+    }
+    args.timeout_given = !unset("timeout") || config.timeout.is_some();
+    if config.stable_only == Some(true) && unset("stable_only") {
+        args.stable_only = true;
     }
-
-    releases.sort_by_key(|v| v.date);
-    Ok(releases)
 }
 
-fn find_pip(
-    client: &Client,
-    pkg: &str,
-    target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    let releases = fetch_pip_releases(client, pkg, verbose)?;
+/// `--mirror` values grouped by manager, in the order given. A bare URL
+/// mirrors `manager`'s registry; `npm=URL` names another one.
+fn parse_mirrors(
+    specs: &[String],
+    manager: Option<Manager>,
+) -> Result<HashMap<Manager, Vec<String>>, String> {
+    let mut mirrors: HashMap<Manager, Vec<String>> = HashMap::new();
+    for spec in specs {
+        let named = spec
+            .split_once('=')
+            .filter(|(name, _)| !name.contains("://"))
+            .map(|(name, url)| {
+                Manager::from_str(name, true)
+                    .map(|m| (m, url))
+                    .map_err(|_| format!("unknown manager '{}' in '{}'", name, spec))
+            })
+            .transpose()?;
+        let (manager, url) = match (named, manager) {
+            (Some(named), _) => named,
+            (None, Some(manager)) => (manager, spec.as_str()),
+            (None, None) => {
+                return Err(format!(
+                    "'{}' doesn't say which registry it mirrors; write it as MANAGER={}",
+                    spec, spec
+                ))
+            }
+        };
+        mirrors
+            .entry(manager)
+            .or_default()
+            .push(url.trim_end_matches('/').to_string());
+    }
+    Ok(mirrors)
+}
 
-    let candidates = releases
-        .into_iter()
-        .filter(|v| v.date <= target_date)
-        .collect();
-    Ok(select_champion(candidates))
+/// Registry URLs given by flag or environment, falling back to the config file.
+fn registry_overrides(args: &Args, config: &Config) -> HashMap<Manager, String> {
+    let npm_url = args
+        .npm_url
+        .clone()
+        .or_else(|| std::env::var("NPM_CONFIG_REGISTRY").ok());
+    let flags = [
+        (Manager::Pip, args.pypi_url.clone()),
+        (Manager::Npm, npm_url),
+        (Manager::Cargo, args.crates_url.clone()),
+        (Manager::Gem, args.rubygems_url.clone()),
+        (Manager::Composer, args.packagist_url.clone()),
+        (Manager::Go, args.goproxy_url.clone()),
+        (Manager::Maven, args.maven_url.clone()),
+        (Manager::NuGet, args.nuget_url.clone()),
+        (Manager::Pub, args.pub_url.clone()),
+        (Manager::Hex, args.hex_url.clone()),
+        (Manager::Cran, args.cran_url.clone()),
+        (Manager::Brew, args.github_url.clone()),
+        (Manager::Swift, args.github_url.clone()),
+        (Manager::Github, args.github_url.clone()),
+        (Manager::Cpan, args.metacpan_url.clone()),
+        (Manager::Apt, args.debian_snapshot_url.clone()),
+        (Manager::Hackage, args.hackage_url.clone()),
+        (Manager::Conda, args.anaconda_url.clone()),
+        (Manager::Pods, args.cocoapods_url.clone()),
+    ];
+
+    let mut urls = config.registries.clone();
+    for (manager, url) in flags {
+        if let Some(url) = url.filter(|u| !u.is_empty()) {
+            urls.insert(manager, url);
+        }
+    }
+    urls
 }
 
-// --- NPM Strategy ---
-#[derive(Deserialize)]
-struct NpmData {
-    time: HashMap<String, String>,
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A unix timestamp cutoff, either `@<seconds>` as `date(1)` takes it or a
+/// bare integer that doesn't already match `format`. The outer `None` means
+/// the input isn't a timestamp at all; the inner one that it's out of range.
+fn parse_timestamp(input: &str, format: &str) -> Option<Option<DateTime<Utc>>> {
+    let s = input.trim();
+    let secs = match s.strip_prefix('@') {
+        Some(secs) => secs,
+        None if NaiveDate::parse_from_str(s, format).is_err() => s,
+        None => return None,
+    };
+    let secs: i64 = secs.parse().ok()?;
+    Some(DateTime::from_timestamp(secs, 0))
 }
 
-fn find_npm(
-    client: &Client,
-    pkg: &str,
-    target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    let url = format!("https://registry.npmjs.org/{}", pkg);
-    if verbose {
-        println!(" -> Fetching {}", url);
+/// Accepts a date in `format` (`YYYY-MM-DD` by default), `today`/`yesterday`,
+/// or a relative offset such as `30d`, `2weeks`, `6mo`, `1y` (optionally
+/// followed by "ago").
+fn parse_cutoff_date(input: &str, today: NaiveDate, format: &str) -> Result<NaiveDate> {
+    let s = input.trim().to_lowercase();
+    match s.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - chrono::Days::new(1)),
+        _ => {}
     }
 
-    let resp = client.get(&url).send()?;
-    if resp.status() == 404 {
-        return Err(anyhow::anyhow!("Package not found on NPM"));
+    let rel = s.strip_suffix("ago").unwrap_or(&s).trim();
+    let split = rel.find(|c: char| !c.is_ascii_digit()).unwrap_or(rel.len());
+    let (num, unit) = rel.split_at(split);
+    if let Ok(n) = num.parse::<u32>() {
+        let date = match unit.trim() {
+            "d" | "day" | "days" => today.checked_sub_days(chrono::Days::new(n.into())),
+            "w" | "wk" | "week" | "weeks" => {
+                today.checked_sub_days(chrono::Days::new(u64::from(n) * 7))
+            }
+            "mo" | "month" | "months" => today.checked_sub_months(chrono::Months::new(n)),
+            "y" | "yr" | "year" | "years" => {
+                today.checked_sub_months(chrono::Months::new(n.saturating_mul(12)))
+            }
+            _ => None,
+        };
+        if let Some(date) = date {
+            return Ok(date);
+        }
     }
-    let data: NpmData = resp.json()?;
 
-    let mut candidates = Vec::new();
-
-    for (version, time_str) in data.time {
-        if version == "created" || version == "modified" {
-            continue;
+    NaiveDate::parse_from_str(input.trim(), format).with_context(|| {
+        if format == DEFAULT_DATE_FORMAT {
+            "Invalid date format. Use YYYY-MM-DD, today, yesterday, or an offset like 30d, 2weeks, 6mo, 1y".to_string()
+        } else {
+            format!(
+                "'{}' doesn't match the date format '{}' (or today, yesterday, or an offset like 30d)",
+                input.trim(),
+                format
+            )
         }
+    })
+}
 
-        // npm dates are usually ISO 8601 with timezone (e.g. 2014-12-23T23:54:33.000Z)
-        if let Ok(date) = DateTime::parse_from_rfc3339(&time_str) {
-            let date_utc = date.with_timezone(&Utc);
-            if date_utc <= target_date {
-                candidates.push(PackageVersion {
-                    version,
-                    date: date_utc,
-                });
+/// The committer date of HEAD in the git repository at `repo`, to the second.
+fn git_head_date(repo: &Path) -> Result<DateTime<Utc>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "-1", "--format=%cI"])
+        .output()
+        .context("Failed to run git (is it installed?)")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git log failed in '{}': {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let date = DateTime::parse_from_rfc3339(raw.trim())
+        .with_context(|| format!("Unexpected commit date {:?} from git", raw.trim()))?;
+    Ok(date.with_timezone(&Utc))
+}
+
+/// Logs go to stderr. `RUST_LOG` wins over `--verbose`, which only changes
+/// the default filter.
+fn init_logging(verbose: u8) {
+    let default = match verbose {
+        0 => "warn",
+        1 => "pkgtime=debug",
+        _ => "pkgtime=trace",
+    };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default)).init();
+}
+
+fn configure_color(choice: ColorChoice) {
+    match choice {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {
+            // https://no-color.org: any non-empty value disables color
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            if no_color || !std::io::stdout().is_terminal() {
+                colored::control::set_override(false);
             }
         }
     }
+}
 
-    Ok(select_champion(candidates))
+/// Resolves every package (with its manager from `managers` and narrowed by
+/// its optional constraint) on a bounded pool of worker threads sharing the
+/// fetcher. Results are returned in the
+/// same order as `packages`; with `fail_fast`, no new packages are started
+/// after a failure and the results end at the first one. `on_result` sees
+/// each result as soon as it is ready.
+#[allow(clippy::too_many_arguments)]
+fn resolve_all(
+    fetcher: &Fetcher,
+    managers: &[Manager],
+    packages: &[String],
+    constraints: &[Option<Constraint>],
+    target_date: DateTime<Utc>,
+    opts: &ResolveOptions,
+    jobs: usize,
+    fail_fast: bool,
+    on_result: &(dyn Fn(usize, &Result<Lookup, ResolveError>) + Sync),
+) -> Vec<Result<Lookup, ResolveError>> {
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let slots: Mutex<Vec<Option<Result<Lookup, ResolveError>>>> =
+        Mutex::new(packages.iter().map(|_| None).collect());
+
+    thread::scope(|s| {
+        for _ in 0..jobs.clamp(1, packages.len().max(1)) {
+            s.spawn(|| loop {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(pkg) = packages.get(idx) else {
+                    break;
+                };
+                let manager = managers[idx];
+                let started = Instant::now();
+                let npm_latest =
+                    manager == Manager::Npm && opts.npm_latest && constraints[idx].is_none();
+                let result = if npm_latest {
+                    npm_latest_at(fetcher, pkg, target_date, opts).map(|chosen| {
+                        let candidates: Vec<PackageVersion> = chosen.iter().cloned().collect();
+                        Lookup {
+                            chosen,
+                            releases: candidates.clone(),
+                            candidates,
+                        }
+                    })
+                } else {
+                    let releases = if manager == Manager::Cargo && opts.cargo_sparse {
+                        cargo_sparse_releases(fetcher, pkg, target_date)
+                    } else {
+                        fetch_releases(fetcher, manager, pkg)
+                    };
+                    releases.and_then(|releases| {
+                        let (chosen, candidates) = pick_at(
+                            manager,
+                            &releases,
+                            constraints[idx].as_ref(),
+                            target_date,
+                            opts,
+                        )?;
+                        Ok(Lookup {
+                            chosen,
+                            candidates,
+                            releases,
+                        })
+                    })
+                };
+                debug!("{} took {:.2?}", pkg, started.elapsed());
+                if fail_fast && is_failure(&result) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                on_result(idx, &result);
+                slots.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+
+    // Packages are claimed in order, so everything before the first failure
+    // has been resolved even when the workers stopped early.
+    let mut results: Vec<Result<Lookup, ResolveError>> = slots
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map_while(|r| r)
+        .collect();
+    if fail_fast {
+        if let Some(first) = results.iter().position(is_failure) {
+            results.truncate(first + 1);
+        }
+    } else {
+        assert_eq!(
+            results.len(),
+            packages.len(),
+            "every package is resolved by a worker"
+        );
+    }
+    results
 }
 
-// --- CARGO Strategy ---
-#[derive(Deserialize)]
-struct CargoVersion {
-    num: String,
-    created_at: String,
+/// The pick at `target_date` among `releases` allowed by `constraint`, with
+/// the candidates it was picked from.
+fn pick_at(
+    manager: Manager,
+    releases: &[PackageVersion],
+    constraint: Option<&Constraint>,
+    target_date: DateTime<Utc>,
+    opts: &ResolveOptions,
+) -> Result<(Option<PackageVersion>, Vec<PackageVersion>), ResolveError> {
+    let candidates = match constraint {
+        Some(c) => {
+            let allowed: Vec<PackageVersion> = releases
+                .iter()
+                .filter(|v| c.matches(&v.version))
+                .cloned()
+                .collect();
+            eligible_versions(manager, &allowed, target_date, opts)?
+        }
+        None => eligible_versions(manager, releases, target_date, opts)?,
+    };
+    let chosen = select_champion(manager, &candidates, opts.select_by, opts.pick);
+    Ok((check_max_age(chosen, target_date, opts)?, candidates))
 }
-#[derive(Deserialize)]
-struct CargoData {
-    versions: Vec<CargoVersion>,
+
+/// `--dates` output: one row per package, one column per cutoff. Histories
+/// were fetched once, each column is just another pick from them.
+fn print_matrix(
+    packages: &[String],
+    managers: &[Manager],
+    constraints: &[Option<Constraint>],
+    results: &[Result<Lookup, ResolveError>],
+    cutoffs: &[DateTime<Utc>],
+    opts: &ResolveOptions,
+) -> bool {
+    let mut complete = true;
+    let mut errors = Vec::new();
+    let rows: Vec<Vec<String>> = packages
+        .iter()
+        .enumerate()
+        .map(|(idx, pkg)| {
+            let lookup = match &results[idx] {
+                Ok(lookup) => lookup,
+                Err(e) => {
+                    complete = false;
+                    errors.push(format!("{}: {}", pkg, e));
+                    return cutoffs.iter().map(|_| "error".to_string()).collect();
+                }
+            };
+            cutoffs
+                .iter()
+                .map(|&cutoff| {
+                    let pick = pick_at(
+                        managers[idx],
+                        &lookup.releases,
+                        constraints[idx].as_ref(),
+                        cutoff,
+                        opts,
+                    );
+                    match pick {
+                        Ok((Some(v), _)) => v.version,
+                        Ok((None, _)) | Err(_) => {
+                            complete = false;
+                            "-".to_string()
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let headers: Vec<String> = cutoffs.iter().map(|c| c.date_naive().to_string()).collect();
+    let name_width = packages.iter().map(|p| p.len()).max().unwrap_or(0).max(7);
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(col, h)| rows.iter().map(|r| r[col].len()).fold(h.len(), usize::max))
+        .collect();
+
+    let mut header = format!("{:<name_width$}", "package");
+    for (h, w) in headers.iter().zip(&widths) {
+        header.push_str(&format!("  {:<w$}", h));
+    }
+    println!("{}", header.trim_end().bold());
+    for (pkg, row) in packages.iter().zip(&rows) {
+        let mut line = format!("{:<name_width$}", pkg).green().to_string();
+        for (cell, w) in row.iter().zip(&widths) {
+            line.push_str(&format!("  {:<w$}", cell));
+        }
+        println!("{}", line.trim_end());
+    }
+
+    if !errors.is_empty() {
+        println!("\n{}", "Attention to errors:".yellow());
+        for err in errors {
+            println!(" - {}", err);
+        }
+    }
+    complete
 }
 
-fn find_cargo(
-    client: &Client,
+fn print_summary(results: &[Result<Lookup, ResolveError>]) {
+    let dates: Vec<DateTime<Utc>> = results
+        .iter()
+        .filter_map(|r| r.as_ref().ok()?.chosen.as_ref())
+        .map(|v| v.date)
+        .collect();
+    let not_found = results
+        .iter()
+        .filter(|r| matches!(r, Ok(Lookup { chosen: None, .. })))
+        .count();
+    let errored = results.iter().filter(|r| r.is_err()).count();
+
+    println!("Resolved:   {}", dates.len().to_string().green());
+    println!("No version: {}", not_found.to_string().yellow());
+    println!("Errors:     {}", errored.to_string().red());
+    if let (Some(oldest), Some(newest)) = (dates.iter().min(), dates.iter().max()) {
+        println!("Oldest:     {}", oldest.date_naive());
+        println!("Newest:     {}", newest.date_naive());
+    }
+}
+
+/// `--interactive`: offers the releases around the cutoff as a numbered menu
+/// on stderr and makes the answer read from `input` the pin. Enter (or end of
+/// input) keeps the automatic pick.
+fn choose_interactively(
     pkg: &str,
+    lookup: &mut Lookup,
     target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    let url = format!("https://crates.io/api/v1/crates/{}", pkg);
-    if verbose {
-        println!(" -> Fetching {}", url);
+    per_side: usize,
+    input: &mut impl BufRead,
+) -> Result<()> {
+    let (before, after) = nearby_versions(
+        &lookup.releases,
+        lookup.chosen.as_ref(),
+        target_date,
+        per_side,
+    );
+    let mut options: Vec<PackageVersion> = before.into_iter().cloned().collect();
+    let picked = lookup.chosen.clone().map(|v| {
+        options.push(v);
+        options.len()
+    });
+    let first_after = options.len();
+    options.extend(after.into_iter().cloned());
+    if options.len() < 2 {
+        return Ok(());
     }
 
-    let resp = client.get(&url).send()?;
-    if resp.status() == 404 {
-        return Err(anyhow::anyhow!("Crate not found on Crates.io"));
+    eprintln!("{}", format!("📦 {}:", pkg).bold());
+    for (idx, v) in options.iter().enumerate() {
+        let mut note = String::new();
+        if idx >= first_after {
+            note.push_str(", after cutoff");
+        }
+        if Some(idx + 1) == picked {
+            note.push_str(", picked");
+        }
+        eprintln!(
+            "  {}) {} ({}{})",
+            idx + 1,
+            v.version,
+            v.date.date_naive(),
+            note
+        );
     }
-    let data: CargoData = resp.json()?;
 
-    let mut candidates = Vec::new();
-
-    for v in data.versions {
-        // "2015-05-06T00:52:16.890333+00:00" - RFC3339 compatible
-        if let Ok(date) = DateTime::parse_from_rfc3339(&v.created_at) {
-            let date_utc = date.with_timezone(&Utc);
-            if date_utc <= target_date {
-                candidates.push(PackageVersion {
-                    version: v.num,
-                    date: date_utc,
-                });
+    loop {
+        match picked {
+            Some(n) => eprint!("Version to pin [{}]: ", n),
+            None => eprint!("Version to pin [none]: "),
+        }
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .context("Failed to read answer")?
+            == 0
+        {
+            eprintln!();
+            return Ok(());
+        }
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Ok(());
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => {
+                lookup.chosen = Some(options.swap_remove(n - 1));
+                return Ok(());
             }
+            _ => eprintln!("Enter a number from 1 to {}", options.len()),
         }
     }
+}
 
-    Ok(select_champion(candidates))
+fn print_nearby(lookup: &Lookup, target_date: DateTime<Utc>, n: usize) {
+    let (before, after) = nearby_versions(&lookup.releases, lookup.chosen.as_ref(), target_date, n);
+    let fmt = |versions: Vec<&PackageVersion>| {
+        if versions.is_empty() {
+            return "-".to_string();
+        }
+        versions
+            .iter()
+            .map(|v| format!("{} ({})", v.version, v.date.date_naive()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    println!("   {} {}", "before cutoff:".dimmed(), fmt(before));
+    println!("   {} {}", "after cutoff: ".dimmed(), fmt(after));
 }
 
-// --- GEM Strategy ---
-#[derive(Deserialize)]
-struct GemVersion {
-    number: String,
-    created_at: String,
+fn read_packages_file(manager: Manager, path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read '{}'", path.display()))?;
+
+    let packages = match manager {
+        Manager::Pip => parse_requirements(&content),
+        Manager::Npm => match path.file_name().and_then(|n| n.to_str()) {
+            Some("yarn.lock") => parse_yarn_lock(&content),
+            Some("pnpm-lock.yaml") => parse_pnpm_lock(&content),
+            _ => parse_package_json(&content)?,
+        },
+        Manager::Cargo => read_cargo_workspace(path, &content)?,
+        Manager::Gem => parse_gemfile(&content),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "--from-file is not supported for '{:?}'",
+                manager
+            ))
+        }
+    };
+
+    Ok(packages)
 }
 
-fn find_gem(
-    client: &Client,
-    pkg: &str,
-    target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    let url = format!("https://rubygems.org/api/v1/versions/{}.json", pkg);
-    if verbose {
-        println!(" -> Fetching {}", url);
-    }
-
-    let resp = client.get(&url).send()?;
-    if resp.status() == 404 {
-        return Err(anyhow::anyhow!("Gem not found on RubyGems"));
-    }
-    // Response is an array of versions
-    let versions: Vec<GemVersion> = resp.json()?;
-
-    let mut candidates = Vec::new();
-
-    for v in versions {
-        // "2015-01-23T19:00:00.000Z"
-        if let Ok(date) = DateTime::parse_from_rfc3339(&v.created_at) {
-            let date_utc = date.with_timezone(&Utc);
-            if date_utc <= target_date {
-                candidates.push(PackageVersion {
-                    version: v.number,
-                    date: date_utc,
-                });
+/// One package per line; blank lines and `#` comments are ignored.
+fn parse_package_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Extracts package names from a requirements.txt, dropping pins, extras,
+/// environment markers, comments and pip options such as `-r` or `--index-url`.
+fn parse_requirements(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .filter_map(|line| {
+            let name: String = line
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+/// Gem names from a Gemfile.lock (its top-level `DEPENDENCIES`, not the
+/// resolved transitive specs) or a plain Gemfile (every `gem` line,
+/// whatever its group or options).
+fn parse_gemfile(content: &str) -> Vec<String> {
+    if content.lines().any(|line| line == "DEPENDENCIES") {
+        return content
+            .lines()
+            .skip_while(|line| *line != "DEPENDENCIES")
+            .skip(1)
+            .take_while(|line| line.starts_with("  "))
+            .filter_map(|line| {
+                // "  rails (~> 6.1)" or "  my_gem!" for path/git sources
+                let name = line.split_whitespace().next()?;
+                Some(name.trim_end_matches('!').to_string())
+            })
+            .collect();
+    }
+
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter_map(|line| {
+            let rest = line.strip_prefix("gem")?;
+            let rest = rest.strip_prefix(['(', ' '])?.trim_start();
+            let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let name = rest[1..].split(quote).next()?;
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn parse_package_json(content: &str) -> Result<Vec<String>> {
+    let json: serde_json::Value = serde_json::from_str(content).context("Invalid package.json")?;
+
+    Ok(json
+        .get("dependencies")
+        .and_then(|d| d.as_object())
+        .map(|deps| deps.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Package names from a yarn.lock, classic or Berry. The lock doesn't say
+/// which packages are direct dependencies, so this is every package in it,
+/// each once.
+fn parse_yarn_lock(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    // Entries start unindented: `"@babel/core@^7.0.0", "@babel/core@^7.1.0":`
+    let headers = content
+        .lines()
+        .filter(|line| !line.starts_with([' ', '#']) && line.ends_with(':'));
+    for header in headers {
+        for spec in header.trim_end_matches(':').split(", ") {
+            let spec = spec.trim_matches('"');
+            // The name ends at the first '@' after a leading scope '@'
+            let Some(at) = spec.char_indices().skip(1).find(|&(_, c)| c == '@') else {
+                continue;
+            };
+            let (name, range) = (&spec[..at.0], &spec[at.0 + 1..]);
+            // Berry lists the project itself (and its workspaces) too
+            if range.starts_with("workspace:") || names.iter().any(|n| n == name) {
+                continue;
             }
+            names.push(name.to_string());
         }
     }
+    names
+}
 
-    Ok(select_champion(candidates))
+/// The root project's `dependencies` from a pnpm-lock.yaml: under
+/// `importers: .:` since lockfile v6, at the top level before.
+fn parse_pnpm_lock(content: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    // Keys of the enclosing mappings, with their indentation
+    let mut path: Vec<(usize, String)> = Vec::new();
+    for line in content.lines() {
+        let text = line.trim_start();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - text.len();
+        while path.last().is_some_and(|(i, _)| *i >= indent) {
+            path.pop();
+        }
+        let key = match text.chars().next() {
+            Some(quote @ ('\'' | '"')) => text[1..].split(quote).next().unwrap_or(""),
+            _ => text.split(':').next().unwrap_or(""),
+        };
+        let parents: Vec<&str> = path.iter().map(|(_, k)| k.as_str()).collect();
+        if matches!(
+            parents[..],
+            ["dependencies"] | ["importers", ".", "dependencies"]
+        ) {
+            names.push(key.to_string());
+        }
+        path.push((indent, key.to_string()));
+    }
+    names
 }
 
-// --- COMPOSER (Packagist) Strategy ---
-#[derive(Deserialize)]
-struct PackagistVersion {
-    time: String,
+const CARGO_DEPENDENCY_TABLES: [&str; 3] =
+    ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// The registry crates a manifest depends on: normal, dev and build
+/// dependencies, including `[target.'cfg(..)'.*]` ones and a workspace
+/// root's `[workspace.dependencies]`. Path and git dependencies aren't on
+/// crates.io and are skipped, as are `workspace = true` entries, which the
+/// root lists.
+fn parse_cargo_toml(content: &str) -> Result<Vec<String>> {
+    let manifest: toml::Table = content.parse().context("Invalid Cargo.toml")?;
+
+    let mut sections = vec![&manifest];
+    if let Some(targets) = manifest.get("target").and_then(|t| t.as_table()) {
+        sections.extend(targets.values().filter_map(|t| t.as_table()));
+    }
+    let mut tables: Vec<&toml::Table> = sections
+        .into_iter()
+        .flat_map(|section| {
+            CARGO_DEPENDENCY_TABLES
+                .iter()
+                .filter_map(|key| section.get(*key).and_then(|d| d.as_table()))
+        })
+        .collect();
+    if let Some(deps) = manifest
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.as_table())
+    {
+        tables.push(deps);
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for (name, spec) in tables.into_iter().flatten() {
+        if ["path", "git", "workspace"]
+            .iter()
+            .any(|key| spec.get(key).is_some())
+        {
+            continue;
+        }
+        // Renamed dependencies: `alias = { package = "real-name", ... }`
+        let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(name);
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
 }
 
-#[derive(Deserialize)]
-struct PackagistPackage {
-    versions: HashMap<String, PackagistVersion>,
+/// `parse_cargo_toml` for the manifest at `path` and, when it is a workspace
+/// root, for every member crate too.
+fn read_cargo_workspace(path: &Path, content: &str) -> Result<Vec<String>> {
+    let mut names = parse_cargo_toml(content)?;
+    let root = path.parent().unwrap_or(Path::new("."));
+    for member in cargo_workspace_members(content, root)? {
+        let manifest = member.join("Cargo.toml");
+        let content = std::fs::read_to_string(&manifest)
+            .with_context(|| format!("Failed to read '{}'", manifest.display()))?;
+        for name in
+            parse_cargo_toml(&content).with_context(|| format!("In '{}'", manifest.display()))?
+        {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
 }
 
-#[derive(Deserialize)]
-struct PackagistWrapper {
-    package: PackagistPackage,
+/// The member directories `[workspace] members` names, relative to `root`.
+/// A `*` in an entry's last component matches any directory there holding a
+/// Cargo.toml, as Cargo expands `crates/*`; `exclude` entries are dropped.
+fn cargo_workspace_members(content: &str, root: &Path) -> Result<Vec<PathBuf>> {
+    let manifest: toml::Table = content.parse().context("Invalid Cargo.toml")?;
+    let Some(workspace) = manifest.get("workspace").and_then(|w| w.as_table()) else {
+        return Ok(Vec::new());
+    };
+    let entries = |key: &str| -> Vec<&str> {
+        workspace
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default()
+    };
+    let excluded: Vec<PathBuf> = entries("exclude").iter().map(|e| root.join(e)).collect();
+
+    let mut members = Vec::new();
+    for entry in entries("members") {
+        let entry = Path::new(entry);
+        let pattern = entry.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some((prefix, suffix)) = pattern.split_once('*') else {
+            members.push(root.join(entry));
+            continue;
+        };
+        let dir = root.join(entry.parent().unwrap_or(Path::new("")));
+        let listing = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to list workspace members in '{}'", dir.display()))?;
+        let mut matched: Vec<PathBuf> = listing
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+                    n.len() >= prefix.len() + suffix.len()
+                        && n.starts_with(prefix)
+                        && n.ends_with(suffix)
+                })
+            })
+            .filter(|p| p.join("Cargo.toml").is_file())
+            .collect();
+        matched.sort();
+        members.extend(matched);
+    }
+    members.retain(|m| !excluded.contains(m));
+    Ok(members)
 }
 
-fn find_composer(
-    client: &Client,
-    pkg: &str,
-    target_date: DateTime<Utc>,
-    verbose: bool,
-) -> Result<Option<PackageVersion>> {
-    let url = format!("https://packagist.org/packages/{}.json", pkg);
-    if verbose {
-        println!(" -> Fetching {}", url);
+/// The `--recursive` dependencies of the resolved pip `packages` not in
+/// `seen` yet, with the specifier of the first package needing each. A
+/// package whose metadata can't be read becomes an error result, so one bad
+/// dependency fails like any other lookup instead of ending the run.
+fn pip_dependencies(
+    fetcher: &Fetcher,
+    packages: &[String],
+    results: &mut [Result<Lookup, ResolveError>],
+    seen: &mut HashSet<String>,
+) -> (Vec<String>, Vec<Option<Constraint>>) {
+    let mut deps = Vec::new();
+    let mut dep_constraints = Vec::new();
+    for (pkg, result) in packages.iter().zip(results.iter_mut()) {
+        let Some(v) = result.as_ref().ok().and_then(|l| l.chosen.as_ref()) else {
+            continue;
+        };
+        let requires = match fetch_pip_requires(fetcher, pkg, &v.version) {
+            Ok(requires) => requires,
+            Err(e) => {
+                let context = |msg| {
+                    format!(
+                        "Failed to read dependencies of {}=={}: {}",
+                        pkg, v.version, msg
+                    )
+                };
+                *result = Err(match e {
+                    ResolveError::NotFound(msg) => ResolveError::NotFound(context(msg)),
+                    ResolveError::Parse(msg) => ResolveError::Parse(context(msg)),
+                    e => e,
+                });
+                continue;
+            }
+        };
+        for (dep, spec) in requires {
+            if seen.insert(normalize_name(Manager::Pip, &dep)) {
+                debug!("{} needs {}", pkg, dep);
+                // A specifier is honoured as written by the first package
+                // needing it, if it's one we understand
+                dep_constraints.push(spec.and_then(|s| Constraint::parse(Manager::Pip, &s).ok()));
+                deps.push(dep);
+            }
+        }
     }
+    (deps, dep_constraints)
+}
 
-    let resp = client.get(&url).send()?;
-    if resp.status() == 404 {
+/// `pkgtime list`: the whole release history, oldest first, whatever the date.
+fn run_list(args: ListArgs, fetcher: &Fetcher) -> Result<()> {
+    let releases = fetch_releases(fetcher, args.manager, &args.package)
+        .with_context(|| format!("Failed to fetch releases for '{}'", args.package))?;
+    println!("📜 {}: {} releases", args.package.green(), releases.len());
+    let width = releases.iter().map(|v| v.version.len()).max().unwrap_or(0);
+    for v in &releases {
+        let line = format!("{:width$}  {}", v.version, v.date.date_naive());
+        if v.yanked {
+            println!("   {} {}", line.dimmed(), "(yanked)".dimmed());
+        } else {
+            println!("   {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// `pkgtime managers`: each manager's name, registry and the base URL that
+/// is queried, with any `--*-url` or config override applied.
+fn print_managers(fetcher: &Fetcher) {
+    for &manager in Manager::value_variants() {
+        println!(
+            "{:<9} {:<28} {}",
+            manager_name(manager).green(),
+            manager.registry_name(),
+            fetcher.registry_url(manager)
+        );
+    }
+}
+
+fn run_overlap(args: OverlapArgs, fetcher: &Fetcher) -> Result<()> {
+    if args.manager != Manager::Pip {
         return Err(anyhow::anyhow!(
-            "Package not found on Packagist (ensure 'vendor/package' format)"
+            "The overlap command is currently only supported for 'pip'"
         ));
     }
 
-    let wrapper: PackagistWrapper = resp.json()?;
+    let (anchor_pkg, anchor_ver) = parse_pip_spec(&args.anchor)?;
 
-    let mut candidates = Vec::new();
+    let anchor_releases = fetch_pip_releases(fetcher, &anchor_pkg).with_context(|| {
+        format!(
+            "Failed to fetch releases for anchor package '{}'",
+            anchor_pkg
+        )
+    })?;
 
-    for (version, data) in wrapper.package.versions {
-        // Filter out dev versions if necessary, but key here is just time
-        // "2021-02-16T14:36:00+00:00"
-        if let Ok(date) = DateTime::parse_from_rfc3339(&data.time) {
-            let date_utc = date.with_timezone(&Utc);
-            if date_utc <= target_date {
-                candidates.push(PackageVersion {
-                    version,
-                    date: date_utc,
-                });
+    let (window_start, window_end) = pip_anchor_window(&anchor_pkg, &anchor_ver, &anchor_releases)?;
+
+    println!(
+        "--- Overlap window for {} ---",
+        format!("{}=={}", anchor_pkg, anchor_ver).yellow()
+    );
+    println!(
+        "Window: {} -> {}",
+        window_start.to_rfc3339().yellow(),
+        window_end.to_rfc3339().yellow()
+    );
+    println!("{}", "-".repeat(60));
+
+    let mut errors = Vec::new();
+
+    for pkg in &args.packages {
+        match fetch_pip_releases(fetcher, pkg) {
+            Ok(releases) => {
+                let overlaps = versions_overlapping_window(&releases, window_start, window_end);
+                if overlaps.is_empty() {
+                    println!(
+                        "{}: {}",
+                        pkg.yellow(),
+                        "no overlapping latest versions".dimmed()
+                    );
+                    continue;
+                }
+
+                let parts: Vec<String> = overlaps
+                    .into_iter()
+                    .map(|o| {
+                        format!(
+                            "{} ({}..{})",
+                            o.version.bold(),
+                            o.overlap_start.date_naive(),
+                            o.overlap_end.date_naive()
+                        )
+                    })
+                    .collect();
+
+                println!("{}: {}", pkg.green(), parts.join(", "));
+            }
+            Err(e) => {
+                println!("❌ {}: {}", pkg.red(), e);
+                errors.push(format!("{}: {}", pkg, e));
             }
         }
     }
 
-    Ok(select_champion(candidates))
-}
+    if !errors.is_empty() {
+        println!("\n{}", "Attention to errors:".yellow());
+        for err in errors {
+            println!(" - {}", err);
+        }
+    }
 
-fn select_champion(mut candidates: Vec<PackageVersion>) -> Option<PackageVersion> {
-    // Sort by date ascending
-    candidates.sort_by_key(|v| v.date);
-    // Return the last one (most recent before cutoff)
-    candidates.pop()
+    Ok(())
 }
 
-fn pip_anchor_window(
-    pkg: &str,
-    version: &str,
-    releases: &[PackageVersion],
-) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
-    let idx = releases
-        .iter()
-        .position(|v| v.version == version)
-        .with_context(|| format!("Version '{}' not found for '{}'", version, pkg))?;
+fn print_json_results(
+    managers: &[Manager],
+    packages: &[String],
+    constraints: &[Option<Constraint>],
+    results: Vec<Result<Lookup, ResolveError>>,
+    cutoff: DateTime<Utc>,
+    list: bool,
+) -> Result<()> {
+    let records: Vec<JsonResult> = (0..results.len())
+        .map(|i| {
+            let constraint = constraints[i].as_ref();
+            json_record(
+                managers[i],
+                &packages[i],
+                &results[i],
+                constraint,
+                cutoff,
+                list,
+            )
+        })
+        .collect();
 
-    let start = releases[idx].date;
-    let end = releases
-        .get(idx + 1)
-        .map(|v| v.date)
-        .unwrap_or_else(Utc::now);
+    println!("{}", serde_json::to_string_pretty(&records)?);
+    Ok(())
+}
 
-    Ok((start, end))
+/// The `--report` JSON. Packages are sorted by manager and name, so two runs
+/// over the same inputs differ only in `generated_at`.
+fn render_report(
+    managers: &[Manager],
+    packages: &[String],
+    constraints: &[Option<Constraint>],
+    results: &[Result<Lookup, ResolveError>],
+    cutoff: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+) -> Result<String> {
+    let mut records: Vec<JsonResult> = (0..results.len())
+        .map(|i| {
+            let constraint = constraints[i].as_ref();
+            json_record(
+                managers[i],
+                &packages[i],
+                &results[i],
+                constraint,
+                cutoff,
+                false,
+            )
+        })
+        .collect();
+    records.sort_by(|a, b| (a.manager, a.package).cmp(&(b.manager, b.package)));
+
+    let report = Report {
+        tool: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        cutoff,
+        generated_at,
+        packages: records,
+    };
+    Ok(serde_json::to_string_pretty(&report)? + "\n")
 }
 
-fn versions_overlapping_window(
-    releases: &[PackageVersion],
-    window_start: DateTime<Utc>,
-    window_end: DateTime<Utc>,
-) -> Vec<WindowedVersion> {
-    if releases.is_empty() || window_start >= window_end {
-        return Vec::new();
+/// With `list`, the record also carries every eligible release.
+fn json_record<'a>(
+    manager: Manager,
+    pkg: &'a str,
+    result: &Result<Lookup, ResolveError>,
+    constraint: Option<&Constraint>,
+    cutoff: DateTime<Utc>,
+    list: bool,
+) -> JsonResult<'a> {
+    let mut record = JsonResult {
+        package: pkg,
+        manager,
+        resolved_version: None,
+        resolved_date: None,
+        error: None,
+        versions: None,
+    };
+    if let (true, Ok(lookup)) = (list, result) {
+        let versions = lookup.candidates.iter().map(|v| JsonVersion {
+            version: v.version.clone(),
+            date: v.date,
+        });
+        record.versions = Some(versions.collect());
     }
+    match result {
+        Ok(Lookup {
+            chosen: Some(v), ..
+        }) => {
+            record.resolved_version = Some(v.version.clone());
+            record.resolved_date = Some(v.date);
+        }
+        Ok(lookup) => record.error = Some(no_version_message(constraint, &lookup.releases, cutoff)),
+        Err(e) => record.error = Some(e.to_string()),
+    }
+    record
+}
 
-    let mut out = Vec::new();
+/// Writes one `--output jsonl` record, flushed so consumers see it right away.
+fn print_json_line(record: &JsonResult) {
+    let line = serde_json::to_string(record).expect("JsonResult always serializes");
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{}", line).and_then(|()| out.flush());
+}
 
-    let mut idx = releases
-        .iter()
-        .rposition(|v| v.date <= window_start)
-        .unwrap_or(0);
+fn install_cmd(manager: Manager, pkg: &str, version: &str) -> String {
+    match manager {
+        Manager::Pip => format!("{}=={}", pkg, version),
+        Manager::Npm => format!("{}@{}", pkg, version),
+        Manager::Cargo => format!("{} = \"={}\"", pkg, version),
+        Manager::Gem => format!("gem '{}', '{}'", pkg, version),
+        Manager::Composer => format!("{}:{}", pkg, version),
+        Manager::Go => format!("{}@{}", pkg, version),
+        Manager::Maven => maven_dependency_snippet(pkg, version),
+        Manager::NuGet => format!("{} --version {}", pkg, version),
+        Manager::Pub => format!("{}: ^{}", pkg, version),
+        Manager::Hex => format!("{{:{}, \"~> {}\"}}", pkg, version),
+        Manager::Cran => format!("remotes::install_version(\"{}\", \"{}\")", pkg, version),
+        Manager::Brew => format!("{}@{}", pkg, version),
+        Manager::Conda => format!("{}={}", pkg, version),
+        Manager::Pods => format!("pod '{}', '{}'", pkg, version),
+        Manager::Swift => swift_package_line(pkg, version),
+        Manager::Cpan => format!("{}@{}", pkg, version),
+        Manager::Apt => format!("{}={}", pkg, version),
+        Manager::Hackage => format!("{} =={}", pkg, version),
+        Manager::Github => format!("gh release download {} --repo {}", version, pkg),
+    }
+}
 
-    loop {
-        let version = &releases[idx];
-        let next_date = releases.get(idx + 1).map(|v| v.date).unwrap_or(window_end);
+/// `--output dockerfile`: one `RUN` line installing every pin, with one
+/// command per manager joined by `&&`. Managers whose pins only go into a
+/// manifest (Cargo.toml, pom.xml, ...) have no such command and are an error.
+fn dockerfile_run(pins: &[(Manager, &str, &str)], pip_no_deps: bool) -> Result<String, String> {
+    let mut managers: Vec<Manager> = Vec::new();
+    for &(manager, ..) in pins {
+        if !managers.contains(&manager) {
+            managers.push(manager);
+        }
+    }
+    let mut commands = Vec::new();
+    for manager in managers {
+        let specs: Vec<String> = pins
+            .iter()
+            .filter(|(m, ..)| *m == manager)
+            .map(|&(_, pkg, version)| match manager {
+                Manager::Gem => format!("{}:{}", pkg, version),
+                _ => install_cmd(manager, pkg, version),
+            })
+            .collect();
+        let joined = specs.join(" ");
+        match manager {
+            Manager::Pip if pip_no_deps => {
+                commands.push(format!("pip install --no-cache-dir --no-deps {}", joined))
+            }
+            Manager::Pip => commands.push(format!("pip install --no-cache-dir {}", joined)),
+            Manager::Npm => commands.push(format!("npm install {}", joined)),
+            Manager::Gem => commands.push(format!("gem install {}", joined)),
+            Manager::Composer => commands.push(format!("composer require {}", joined)),
+            Manager::Go => commands.push(format!("go get {}", joined)),
+            Manager::Conda => commands.push(format!("conda install -y {}", joined)),
+            Manager::Cpan => commands.push(format!("cpanm {}", joined)),
+            Manager::Apt => {
+                commands.push(format!("apt-get update && apt-get install -y {}", joined))
+            }
+            Manager::NuGet => {
+                commands.extend(specs.iter().map(|s| format!("dotnet add package {}", s)))
+            }
+            Manager::Cran => {
+                commands.push("Rscript -e 'install.packages(\"remotes\")'".to_string());
+                commands.extend(specs.iter().map(|s| format!("Rscript -e '{}'", s)));
+            }
+            Manager::Github => commands.extend(specs),
+            _ => {
+                return Err(format!(
+                    "{} pins go into a manifest, there is no command to install them",
+                    manager_name(manager)
+                ))
+            }
+        }
+    }
+    Ok(format!("RUN {}", commands.join(" && ")))
+}
+
+/// Renders the resolved pins in the manager's own manifest/lockfile format.
+fn render_lockfile(manager: Manager, pins: &[(&str, &str)]) -> String {
+    let mut out = String::new();
+    match manager {
+        Manager::Pip => {
+            for (pkg, version) in pins {
+                out.push_str(&format!("{}=={}\n", pkg, version));
+            }
+        }
+        Manager::Npm | Manager::Composer => {
+            let deps: serde_json::Map<String, serde_json::Value> = pins
+                .iter()
+                .map(|(pkg, version)| (pkg.to_string(), version.to_string().into()))
+                .collect();
+            let section = if manager == Manager::Npm {
+                "dependencies"
+            } else {
+                "require"
+            };
+            let doc = serde_json::json!({ section: deps });
+            out = serde_json::to_string_pretty(&doc).unwrap_or_default();
+            out.push('\n');
+        }
+        Manager::Cargo => {
+            out.push_str("[dependencies]\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("{} = \"={}\"\n", pkg, version));
+            }
+        }
+        Manager::Gem => {
+            out.push_str("source 'https://rubygems.org'\n\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("gem '{}', '{}'\n", pkg, version));
+            }
+        }
+        Manager::Go => {
+            out.push_str("require (\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("\t{} {}\n", pkg, version));
+            }
+            out.push_str(")\n");
+        }
+        Manager::Maven => {
+            out.push_str("<dependencies>\n");
+            for (pkg, version) in pins {
+                for line in maven_dependency_snippet(pkg, version).lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+            out.push_str("</dependencies>\n");
+        }
+        Manager::NuGet => {
+            out.push_str("<ItemGroup>\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!(
+                    "    <PackageReference Include=\"{}\" Version=\"{}\" />\n",
+                    pkg, version
+                ));
+            }
+            out.push_str("</ItemGroup>\n");
+        }
+        Manager::Pub => {
+            out.push_str("dependencies:\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("  {}: ^{}\n", pkg, version));
+            }
+        }
+        Manager::Hex => {
+            out.push_str("defp deps do\n  [\n");
+            let deps: Vec<String> = pins
+                .iter()
+                .map(|(pkg, version)| format!("    {}", install_cmd(manager, pkg, version)))
+                .collect();
+            out.push_str(&deps.join(",\n"));
+            out.push_str("\n  ]\nend\n");
+        }
+        Manager::Cran => {
+            out.push_str("install.packages(\"remotes\")\n");
+            for (pkg, version) in pins {
+                out.push_str(&install_cmd(manager, pkg, version));
+                out.push('\n');
+            }
+        }
+        Manager::Brew => {
+            out.push_str(&brew_extract_script(pins));
+        }
+        Manager::Conda => {
+            out.push_str("dependencies:\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("  - {}\n", install_cmd(manager, pkg, version)));
+            }
+        }
+        Manager::Pods => {
+            for (pkg, version) in pins {
+                out.push_str(&install_cmd(manager, pkg, version));
+                out.push('\n');
+            }
+        }
+        Manager::Swift => {
+            out.push_str("dependencies: [\n");
+            for (pkg, version) in pins {
+                out.push_str(&format!("    {},\n", install_cmd(manager, pkg, version)));
+            }
+            out.push_str("]\n");
+        }
+        Manager::Cpan => {
+            for (pkg, version) in pins {
+                out.push_str(&format!("requires '{}', '== {}';\n", pkg, version));
+            }
+        }
+        Manager::Apt => {
+            for (pkg, version) in pins {
+                out.push_str(&format!("{}={}\n", pkg, version));
+            }
+        }
+        Manager::Hackage => {
+            // cabal.project.freeze
+            let lines: Vec<String> = pins
+                .iter()
+                .map(|(pkg, version)| format!("any.{} =={}", pkg, version))
+                .collect();
+            out.push_str(&format!(
+                "constraints: {}\n",
+                lines.join(",\n             ")
+            ));
+        }
+        Manager::Github => {
+            for (pkg, version) in pins {
+                out.push_str(&format!("{} {}\n", pkg, version));
+            }
+        }
+    }
+    out
+}
 
-        let overlap_start = std::cmp::max(version.date, window_start);
-        let overlap_end = std::cmp::min(next_date, window_end);
+/// The `--group-output` section header for `manager`.
+fn manager_header(manager: Manager) -> String {
+    format!("## {} ({})", manager_name(manager), manager.registry_name())
+        .bold()
+        .to_string()
+}
 
-        if overlap_start < overlap_end {
-            out.push(WindowedVersion {
-                version: version.version.clone(),
-                overlap_start,
-                overlap_end,
-            });
+/// Prints the install instructions for each manager's pins (a single group
+/// unless `--mixed`).
+fn print_install_instructions(groups: &[(Manager, Vec<String>)], headers: bool, pip_no_deps: bool) {
+    println!("Copy and paste into your configuration:");
+    for (manager, cmds) in groups {
+        println!();
+        if headers {
+            println!("{}", manager_header(*manager));
         }
+        print_manager_instructions(*manager, cmds, pip_no_deps);
+    }
+    println!();
+}
 
-        idx += 1;
-        if idx >= releases.len() {
-            break;
+fn print_manager_instructions(manager: Manager, cmds: &[String], pip_no_deps: bool) {
+    match manager {
+        // --hash only works inside a requirements file
+        Manager::Pip if cmds.iter().any(|c| c.contains(" --hash=")) => {
+            let no_deps = if pip_no_deps { " --no-deps" } else { "" };
+            println!(
+                "{}",
+                format!(
+                    "# requirements.txt, for pip install --require-hashes{} -r requirements.txt:",
+                    no_deps
+                )
+                .bright_green()
+            );
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
         }
-        if releases[idx].date >= window_end {
-            break;
+        Manager::Pip if pip_no_deps => println!(
+            "{}",
+            format!("pip install --no-deps {}", cmds.join(" ")).bright_green()
+        ),
+        Manager::Pip => println!(
+            "{}",
+            format!("pip install {}", cmds.join(" ")).bright_green()
+        ),
+        Manager::Npm => println!(
+            "{}",
+            format!("npm install {}", cmds.join(" ")).bright_green()
+        ),
+        Manager::Cargo => {
+            println!("{}", "# Cargo.toml dependencies:".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::Gem => {
+            println!("{}", "# Gemfile:".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::Composer => {
+            println!(
+                "{}",
+                format!("composer require {}", cmds.join(" ")).bright_green()
+            );
+        }
+        Manager::Go => {
+            for cmd in cmds {
+                println!("{}", format!("go get {}", cmd).bright_green());
+            }
+        }
+        Manager::Maven => {
+            println!("{}", "<!-- pom.xml dependencies: -->".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::NuGet => {
+            for cmd in cmds {
+                println!("{}", format!("dotnet add package {}", cmd).bright_green());
+            }
+        }
+        Manager::Pub => {
+            println!("{}", "# pubspec.yaml dependencies:".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::Hex => {
+            println!("{}", "# mix.exs deps:".bright_green());
+            for cmd in cmds {
+                println!("{}", format!("{},", cmd).bright_green());
+            }
+        }
+        Manager::Cran => {
+            println!(
+                "{}",
+                "# install.packages(\"pkg\") always installs the latest release; to pin the resolved versions:"
+                    .bright_green()
+            );
+            println!("{}", "install.packages(\"remotes\")".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::Brew => {
+            println!(
+                "{}",
+                "# Homebrew only installs current formulae; extract the resolved versions into a local tap:"
+                    .bright_green()
+            );
+            let pins: Vec<(&str, &str)> = cmds.iter().filter_map(|c| c.rsplit_once('@')).collect();
+            print!("{}", brew_extract_script(&pins).bright_green());
+        }
+        Manager::Conda => println!(
+            "{}",
+            format!("conda install {}", cmds.join(" ")).bright_green()
+        ),
+        Manager::Pods => {
+            println!("{}", "# Podfile:".bright_green());
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
+        }
+        Manager::Swift => {
+            println!("{}", "// Package.swift dependencies:".bright_green());
+            for cmd in cmds {
+                println!("{}", format!("{},", cmd).bright_green());
+            }
+        }
+        Manager::Cpan => {
+            println!("{}", format!("cpanm {}", cmds.join(" ")).bright_green());
+        }
+        Manager::Apt => {
+            println!(
+                "{}",
+                "# the versions may only be installable from snapshot.debian.org:".bright_green()
+            );
+            println!(
+                "{}",
+                format!("apt-get install {}", cmds.join(" ")).bright_green()
+            );
+        }
+        Manager::Hackage => {
+            println!("{}", "-- cabal.project:".bright_green());
+            println!(
+                "{}",
+                format!("constraints: {}", cmds.join(", ")).bright_green()
+            );
+        }
+        Manager::Github => {
+            println!(
+                "{}",
+                "# Download the release assets with the GitHub CLI:".bright_green()
+            );
+            for cmd in cmds {
+                println!("{}", cmd.bright_green());
+            }
         }
     }
+}
 
+/// `brew extract` recreates an old formula version in a local tap.
+fn brew_extract_script(pins: &[(&str, &str)]) -> String {
+    let mut out = String::from("brew tap-new $USER/local\n");
+    for (pkg, version) in pins {
+        out.push_str(&format!(
+            "brew extract --version={} {} $USER/local\n",
+            version, pkg
+        ));
+    }
+    let formulae: Vec<String> = pins
+        .iter()
+        .map(|(pkg, version)| format!("{}@{}", pkg, version))
+        .collect();
+    out.push_str(&format!("brew install {}\n", formulae.join(" ")));
     out
 }
 
+/// A requirements.txt line pinning `version` to the files with `hashes`.
+fn hashed_requirement(pkg: &str, version: &str, hashes: &[String]) -> String {
+    let mut line = format!("{}=={}", pkg, version);
+    for hash in hashes {
+        line.push_str(&format!(" \\\n    --hash=sha256:{}", hash));
+    }
+    line
+}
+
+fn swift_package_line(pkg: &str, version: &str) -> String {
+    let url = match github_repo(pkg) {
+        Some((owner, repo)) => format!("https://github.com/{}/{}.git", owner, repo),
+        None => pkg.to_string(),
+    };
+    format!(".package(url: \"{}\", from: \"{}\")", url, version)
+}
+
+fn maven_dependency_snippet(coords: &str, version: &str) -> String {
+    let (group, artifact) = coords.split_once(':').unwrap_or((coords, coords));
+    format!(
+        "<dependency>\n    <groupId>{}</groupId>\n    <artifactId>{}</artifactId>\n    <version>{}</version>\n</dependency>",
+        group, artifact, version
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{TimeZone, Utc};
 
-    #[test]
-    fn parse_pip_spec_ok() {
-        let (n, v) = parse_pip_spec("requests==2.31.0").unwrap();
-        assert_eq!(n, "requests");
-        assert_eq!(v, "2.31.0");
-    }
-
-    #[test]
-    fn parse_pip_spec_rejects_invalid() {
-        assert!(parse_pip_spec("requests").is_err());
-        assert!(parse_pip_spec("==1.0.0").is_err());
-        assert!(parse_pip_spec("requests==").is_err());
+    /// Midnight UTC on the given day.
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
     }
 
     fn pv(version: &str, y: i32, m: u32, d: u32) -> PackageVersion {
         PackageVersion {
             version: version.to_string(),
-            date: Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap(),
+            date: at(y, m, d),
+            yanked: false,
         }
     }
 
     #[test]
-    fn versions_overlapping_window_spans_multiple_versions() {
+    fn parse_package_list_skips_blanks_and_comments() {
+        let input = "# from stdin\nrequests\n\n  flask  # web\n";
+        assert_eq!(parse_package_list(input), vec!["requests", "flask"]);
+    }
+
+    #[test]
+    fn split_mixed_takes_manager_prefix() {
+        assert_eq!(split_mixed("pip:requests"), Ok((Manager::Pip, "requests")));
+        assert_eq!(
+            split_mixed("maven:com.google.guava:guava"),
+            Ok((Manager::Maven, "com.google.guava:guava"))
+        );
+        assert_eq!(
+            split_mixed("conda:bioconda::samtools"),
+            Ok((Manager::Conda, "bioconda::samtools"))
+        );
+        assert!(split_mixed("requests").is_err());
+        assert!(split_mixed("snap:curl").is_err());
+    }
+
+    #[test]
+    fn exit_status_separates_misses_from_lookup_failures() {
+        let miss = || {
+            Err(ResolveError::NotFound(
+                "Package not found on PyPI".to_string(),
+            ))
+        };
+        let timeout = || {
+            Err(ResolveError::Timeout {
+                registry: "PyPI",
+                secs: 30,
+            })
+        };
+        let none = || {
+            Ok(Lookup {
+                chosen: None,
+                candidates: Vec::new(),
+                releases: Vec::new(),
+            })
+        };
+        assert_eq!(exit_status(&[]), 0);
+        assert_eq!(exit_status(&[miss(), none()]), 1);
+        assert_eq!(exit_status(&[miss(), timeout()]), 3);
+    }
+
+    #[test]
+    fn result_order_sorts_by_date_with_failures_last() {
+        let lookup = |version: &str, day: u32| {
+            Ok(Lookup {
+                chosen: Some(pv(version, 2020, 1, day)),
+                candidates: Vec::new(),
+                releases: Vec::new(),
+            })
+        };
+        let packages: Vec<String> = ["b", "missing", "a", "C"].map(String::from).to_vec();
+        let results = vec![
+            lookup("1.0", 20),
+            Err(ResolveError::NotFound("not found".to_string())),
+            lookup("2.0", 5),
+            lookup("3.0", 10),
+        ];
+        assert_eq!(
+            result_order(&packages, &results, SortOrder::Input),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            result_order(&packages, &results, SortOrder::Name),
+            [2, 0, 3, 1]
+        );
+        assert_eq!(
+            result_order(&packages, &results, SortOrder::Date),
+            [2, 3, 0, 1]
+        );
+    }
+
+    #[test]
+    fn config_overrides_only_defaults() {
+        let config: Config =
+            toml::from_str("manager = \"npm\"\njobs = 2\ntimeout = 5\nstable_only = true\n")
+                .unwrap();
+        assert_eq!(config.manager, Some(Manager::Npm));
+
+        let matches = Args::command()
+            .try_get_matches_from(["piptime", "npm", "2024-01-01", "react", "--jobs", "16"])
+            .unwrap();
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        apply_config(&mut args, &config, &matches);
+        assert_eq!(args.jobs, 16);
+        assert_eq!(args.timeout, 5);
+        assert!(args.stable_only);
+    }
+
+    #[test]
+    fn registry_flags_override_config() {
+        let config: Config = toml::from_str(
+            "[registries]\npip = \"https://config.example/pypi\"\ncargo = \"https://config.example/crates\"\n",
+        )
+        .unwrap();
+        let args = Args::try_parse_from([
+            "piptime",
+            "pip",
+            "2024-01-01",
+            "requests",
+            "--pypi-url",
+            "https://cli.example",
+        ])
+        .unwrap();
+        let urls = registry_overrides(&args, &config);
+        assert_eq!(urls[&Manager::Pip], "https://cli.example");
+        assert_eq!(urls[&Manager::Cargo], "https://config.example/crates");
+    }
+
+    #[test]
+    fn parse_requirements_strips_pins_and_comments() {
+        let content = "\
+# pinned deps
+requests==2.25.0
+Django>=3.0,<4  # web
+-r other.txt
+--index-url https://example.com/simple
+
+urllib3[secure]; python_version >= \"3.6\"
+";
+        assert_eq!(
+            parse_requirements(content),
+            vec!["requests", "Django", "urllib3"]
+        );
+    }
+
+    #[test]
+    fn choose_interactively_pins_answer() {
         let releases = vec![
-            pv("1.0.0", 2020, 1, 1),
-            pv("1.1.0", 2020, 2, 1),
-            pv("2.0.0", 2020, 3, 1),
+            pv("1.0", 2020, 1, 1),
+            pv("1.1", 2020, 1, 2),
+            pv("2.0", 2020, 1, 9),
+        ];
+        let lookup = || Lookup {
+            chosen: Some(releases[1].clone()),
+            candidates: releases[..2].to_vec(),
+            releases: releases.clone(),
+        };
+
+        // An invalid answer is asked again; 3 is the release after the cutoff
+        let mut chosen = lookup();
+        let mut input = std::io::Cursor::new("7\n3\n");
+        choose_interactively("pkg", &mut chosen, at(2020, 1, 5), 3, &mut input).unwrap();
+        assert_eq!(chosen.chosen.unwrap().version, "2.0");
+
+        for answer in ["\n", ""] {
+            let mut kept = lookup();
+            let mut input = std::io::Cursor::new(answer);
+            choose_interactively("pkg", &mut kept, at(2020, 1, 5), 3, &mut input).unwrap();
+            assert_eq!(kept.chosen.unwrap().version, "1.1");
+        }
+    }
+
+    #[test]
+    fn parse_gemfile_reads_lockfile_and_gemfile() {
+        let lock = "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    rack (2.2.3)
+    rails (6.1.4)
+      rack (~> 2.0)
+
+PLATFORMS
+  ruby
+
+DEPENDENCIES
+  rails (~> 6.1)
+  local_gem!
+  puma
+
+BUNDLED WITH
+   2.2.22
+";
+        assert_eq!(parse_gemfile(lock), vec!["rails", "local_gem", "puma"]);
+
+        let gemfile = r#"
+source "https://rubygems.org"
+gem "rails", "~> 6.1"
+gem 'pg', '>= 0.18', require: false # database
+
+group :development, :test do
+  gem "rspec-rails"
+end
+gems_path = "vendor"
+"#;
+        assert_eq!(parse_gemfile(gemfile), vec!["rails", "pg", "rspec-rails"]);
+    }
+
+    #[test]
+    fn parse_package_json_reads_dependency_keys() {
+        let content =
+            r#"{"name": "app", "dependencies": {"express": "^4.17.1", "@types/node": "*"}}"#;
+        let mut got = parse_package_json(content).unwrap();
+        got.sort();
+        assert_eq!(got, vec!["@types/node", "express"]);
+    }
+
+    #[test]
+    fn parse_yarn_lock_collects_every_package() {
+        let classic = r#"# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+  version "7.10.4"
+  dependencies:
+    "@babel/highlight" "^7.10.4"
+
+lodash@^4.17.20, lodash@^4.17.21:
+  version "4.17.21"
+"#;
+        assert_eq!(parse_yarn_lock(classic), ["@babel/code-frame", "lodash"]);
+
+        let berry = r#"__metadata:
+  version: 6
+
+"lodash@npm:^4.17.21":
+  version: 4.17.21
+
+"my-app@workspace:.":
+  version: 0.0.0-use.local
+"#;
+        assert_eq!(parse_yarn_lock(berry), ["lodash"]);
+    }
+
+    #[test]
+    fn parse_pnpm_lock_reads_root_dependencies() {
+        let v9 = "\
+lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      '@types/node':
+        specifier: ^20.0.0
+        version: 20.1.0
+      react:
+        specifier: ^18.2.0
+        version: 18.2.0
+    devDependencies:
+      typescript:
+        specifier: ^5.0.0
+        version: 5.0.4
+
+  packages/lib:
+    dependencies:
+      left-pad:
+        specifier: ^1.3.0
+        version: 1.3.0
+
+packages:
+
+  react@18.2.0:
+    dependencies:
+      loose-envify: 1.4.0
+";
+        assert_eq!(parse_pnpm_lock(v9), ["@types/node", "react"]);
+
+        let v5 = "\
+lockfileVersion: 5.4
+
+specifiers:
+  react: ^18.2.0
+
+dependencies:
+  react: 18.2.0
+
+packages:
+
+  /loose-envify/1.4.0:
+    dependencies:
+      js-tokens: 4.0.0
+";
+        assert_eq!(parse_pnpm_lock(v5), ["react"]);
+    }
+
+    #[test]
+    fn render_template_substitutes_known_fields() {
+        let values = [("name", "requests"), ("version", "2.22.0")];
+        assert_eq!(
+            render_template("<{name}> {version} {{x}} {", &values),
+            "<requests> 2.22.0 {{x}} {"
+        );
+        assert_eq!(
+            unknown_placeholder("{name} {verison}", &TEMPLATE_FIELDS),
+            Some("verison")
+        );
+        assert_eq!(
+            unknown_placeholder("{name}=={version} {\"json\": 1}", &TEMPLATE_FIELDS),
+            None
+        );
+    }
+
+    #[test]
+    fn no_version_message_names_the_baseline() {
+        let constraint = |spec| Constraint::parse(Manager::Pip, spec).unwrap();
+        let releases = [pv("1.0.0", 2020, 1, 1), pv("2.0.0", 2021, 1, 1)];
+        let cutoff = at(2020, 6, 1);
+        assert_eq!(
+            no_version_message(Some(&constraint(">1.2.3")), &releases, cutoff),
+            "No version newer than 1.2.3 before the specified date"
+        );
+        assert_eq!(
+            no_version_message(Some(&constraint(">1.2,<2")), &releases, cutoff),
+            "No version matching '>1.2,<2' before the specified date"
+        );
+        assert_eq!(
+            no_version_message(None, &releases, at(2019, 6, 1)),
+            "Package exists, but its first release (1.0.0) was on 2020-01-01, after the cutoff"
+        );
+        assert_eq!(
+            no_version_message(None, &[], cutoff),
+            "No version found before the specified date"
+        );
+        let semver = Constraint::parse(Manager::Cargo, ">1.0.100").unwrap();
+        assert_eq!(semver.baseline(), Some("1.0.100"));
+        assert_eq!(constraint(">=1.2").baseline(), None);
+    }
+
+    #[test]
+    fn dockerfile_run_joins_managers() {
+        let pins = [
+            (Manager::Pip, "requests", "2.22.0"),
+            (Manager::Npm, "react", "16.8.6"),
+            (Manager::Pip, "flask", "1.0.3"),
         ];
-        let window_start = Utc.with_ymd_and_hms(2020, 1, 15, 0, 0, 0).unwrap();
-        let window_end = Utc.with_ymd_and_hms(2020, 2, 15, 0, 0, 0).unwrap();
+        assert_eq!(
+            dockerfile_run(&pins, false).unwrap(),
+            "RUN pip install --no-cache-dir requests==2.22.0 flask==1.0.3 && npm install react@16.8.6"
+        );
+        assert!(dockerfile_run(&[(Manager::Cargo, "serde", "1.0.0")], false).is_err());
+    }
 
-        let got = versions_overlapping_window(&releases, window_start, window_end);
-        assert_eq!(got.len(), 2);
-        assert_eq!(got[0].version, "1.0.0");
-        assert_eq!(got[0].overlap_start, window_start);
-        assert_eq!(got[0].overlap_end, releases[1].date);
-        assert_eq!(got[1].version, "1.1.0");
-        assert_eq!(got[1].overlap_start, releases[1].date);
-        assert_eq!(got[1].overlap_end, window_end);
+    #[test]
+    fn parse_cutoff_date_relative() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let parse = |input: &str, today| parse_cutoff_date(input, today, DEFAULT_DATE_FORMAT);
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(parse("today", today).unwrap(), today);
+        assert_eq!(parse("yesterday", today).unwrap(), d(2024, 3, 30));
+        assert_eq!(parse("30d", today).unwrap(), d(2024, 3, 1));
+        assert_eq!(parse("2weeks", today).unwrap(), d(2024, 3, 17));
+        assert_eq!(parse("1mo", today).unwrap(), d(2024, 2, 29));
+        assert_eq!(parse("6 months ago", today).unwrap(), d(2023, 9, 30));
+        assert_eq!(parse("1y", today).unwrap(), d(2023, 3, 31));
+        assert_eq!(parse("2020-01-15", today).unwrap(), d(2020, 1, 15));
+        assert!(parse("5 fortnights", today).is_err());
+        assert!(parse("2020/01/15", today).is_err());
+
+        assert_eq!(
+            parse_cutoff_date("15/01/2020", today, "%d/%m/%Y").unwrap(),
+            d(2020, 1, 15)
+        );
+        let err = parse_cutoff_date("2020-01-15", today, "%d/%m/%Y").unwrap_err();
+        assert!(err.to_string().contains("'%d/%m/%Y'"), "{}", err);
     }
 
     #[test]
-    fn versions_overlapping_window_start_before_first_release() {
-        let releases = vec![pv("0.1.0", 2020, 2, 1)];
-        let window_start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
-        let window_end = Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0).unwrap();
-
-        let got = versions_overlapping_window(&releases, window_start, window_end);
-        assert_eq!(got.len(), 1);
-        assert_eq!(got[0].version, "0.1.0");
-        assert_eq!(got[0].overlap_start, releases[0].date);
-        assert_eq!(got[0].overlap_end, window_end);
+    fn parse_mirrors_by_manager() {
+        let specs = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let got = parse_mirrors(
+            &specs(&[
+                "https://a.example/pypi/",
+                "npm=https://npm.example",
+                "https://b.example",
+            ]),
+            Some(Manager::Pip),
+        )
+        .unwrap();
+        assert_eq!(
+            got[&Manager::Pip],
+            ["https://a.example/pypi", "https://b.example"]
+        );
+        assert_eq!(got[&Manager::Npm], ["https://npm.example"]);
+
+        // A query string's `=` isn't a manager prefix
+        let got = parse_mirrors(&specs(&["https://m.example/?a=b"]), Some(Manager::Go)).unwrap();
+        assert_eq!(got[&Manager::Go], ["https://m.example/?a=b"]);
+
+        assert!(parse_mirrors(&specs(&["https://m.example"]), None).is_err());
+        assert!(parse_mirrors(&specs(&["pipp=https://m.example"]), None).is_err());
+    }
+
+    #[test]
+    fn parse_timestamp_forms() {
+        let at = |secs| Some(DateTime::from_timestamp(secs, 0));
+        assert_eq!(
+            parse_timestamp("1577836800", DEFAULT_DATE_FORMAT),
+            at(1577836800)
+        );
+        assert_eq!(
+            parse_timestamp(" @1577836800 ", DEFAULT_DATE_FORMAT),
+            at(1577836800)
+        );
+        assert_eq!(parse_timestamp("@-86400", DEFAULT_DATE_FORMAT), at(-86400));
+        assert_eq!(parse_timestamp("2020-01-01", DEFAULT_DATE_FORMAT), None);
+        assert_eq!(parse_timestamp("30d", DEFAULT_DATE_FORMAT), None);
+        // A compact date format wins over reading the digits as seconds
+        assert_eq!(parse_timestamp("20200101", "%Y%m%d"), None);
+        assert_eq!(parse_timestamp("@20200101", "%Y%m%d"), at(20200101));
+        assert_eq!(
+            parse_timestamp("@99999999999999999", DEFAULT_DATE_FORMAT),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn pip_dependencies_survive_missing_metadata() {
+        let mut fetcher = Fetcher::new(Duration::from_secs(5)).unwrap();
+        fetcher.offline = Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+        let pinned = |version: &str| {
+            Ok(Lookup {
+                chosen: Some(pv(version, 2020, 1, 1)),
+                candidates: Vec::new(),
+                releases: Vec::new(),
+            })
+        };
+        // There is no metadata fixture for flask 1.1.1
+        let packages = ["flask", "requests"].map(String::from);
+        let mut results = vec![pinned("1.1.1"), pinned("2.22.0")];
+        let mut seen: HashSet<String> = packages.iter().cloned().collect();
+
+        let (deps, constraints) = pip_dependencies(&fetcher, &packages, &mut results, &mut seen);
+        assert_eq!(deps, ["chardet", "idna", "urllib3", "certifi"]);
+        assert_eq!(constraints.len(), deps.len());
+        let err = results[0].as_ref().err().expect("flask's lookup failed");
+        assert!(matches!(err, ResolveError::NotFound(_)));
+        assert!(err
+            .to_string()
+            .starts_with("Failed to read dependencies of flask==1.1.1"));
+        assert!(results[1].is_ok());
+    }
+
+    #[test]
+    fn json_record_explains_a_missing_pick() {
+        let result = Ok(Lookup {
+            chosen: None,
+            candidates: Vec::new(),
+            releases: vec![pv("1.0.0", 2021, 1, 1)],
+        });
+        let record = json_record(Manager::Pip, "pkg", &result, None, at(2020, 1, 1), false);
+        assert_eq!(
+            record.error.as_deref(),
+            Some(
+                "Package exists, but its first release (1.0.0) was on 2021-01-01, after the cutoff"
+            )
+        );
+
+        let baseline = Constraint::parse(Manager::Pip, ">0.9").unwrap();
+        let record = json_record(
+            Manager::Pip,
+            "pkg",
+            &result,
+            Some(&baseline),
+            at(2022, 1, 1),
+            false,
+        );
+        assert_eq!(
+            record.error.as_deref(),
+            Some("No version newer than 0.9 before the specified date")
+        );
+    }
+
+    #[test]
+    fn render_report_sorts_packages() {
+        let results = vec![
+            Ok(Lookup {
+                chosen: Some(pv("2.22.0", 2020, 1, 2)),
+                candidates: Vec::new(),
+                releases: Vec::new(),
+            }),
+            Err(ResolveError::NotFound(
+                "Package not found on NPM".to_string(),
+            )),
+            Err(ResolveError::NotFound(
+                "Package not found on PyPI".to_string(),
+            )),
+        ];
+        let managers = [Manager::Pip, Manager::Npm, Manager::Pip];
+        let packages = ["requests", "left-pad", "flask"].map(String::from);
+        let constraints = [None, None, None];
+        let report = render_report(
+            &managers,
+            &packages,
+            &constraints,
+            &results,
+            at(2020, 1, 3),
+            at(2020, 1, 4),
+        )
+        .unwrap();
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(report["cutoff"], "2020-01-03T00:00:00Z");
+        let names: Vec<&str> = report["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["package"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["flask", "requests", "left-pad"]);
+        assert_eq!(report["packages"][1]["resolved_version"], "2.22.0");
+        assert_eq!(report["packages"][2]["error"], "Package not found on NPM");
+    }
+
+    #[test]
+    fn render_lockfile_formats() {
+        let pins = [("requests", "2.22.0"), ("idna", "2.8")];
+        assert_eq!(
+            render_lockfile(Manager::Pip, &pins),
+            "requests==2.22.0\nidna==2.8\n"
+        );
+        assert_eq!(
+            render_lockfile(Manager::Cargo, &[("serde", "1.0.126")]),
+            "[dependencies]\nserde = \"=1.0.126\"\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_lockfile(Manager::Npm, &[("express", "4.17.1")])).unwrap();
+        assert_eq!(json["dependencies"]["express"], "4.17.1");
+        let json: serde_json::Value = serde_json::from_str(&render_lockfile(
+            Manager::Composer,
+            &[("monolog/monolog", "2.2.0")],
+        ))
+        .unwrap();
+        assert_eq!(json["require"]["monolog/monolog"], "2.2.0");
+    }
+
+    #[test]
+    fn parse_cargo_toml_reads_dependency_keys() {
+        let content = r#"
+[package]
+name = "app"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+anyhow = "1.0"
+json = { package = "serde_json", version = "1.0" }
+
+local = { path = "../local" }
+forked = { git = "https://github.com/example/forked" }
+shared = { workspace = true }
+
+[dev-dependencies]
+tempfile = "3"
+
+[build-dependencies]
+cc = "1"
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+"#;
+        let mut got = parse_cargo_toml(content).unwrap();
+        got.sort();
+        assert_eq!(
+            got,
+            vec!["anyhow", "cc", "serde", "serde_json", "tempfile", "winapi"]
+        );
+    }
+
+    #[test]
+    fn read_cargo_workspace_follows_members() {
+        let root = std::env::temp_dir().join(format!("pkgtime-ws-{}", std::process::id()));
+        let member = |dir: &str, deps: &str| {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            let manifest = format!("[package]\nname = \"m\"\n\n[dependencies]\n{}\n", deps);
+            std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+        };
+        member("crates/core", "serde = \"1\"\nlog.workspace = true");
+        member("crates/cli", "clap = \"4\"\nserde = \"1\"");
+        member("crates/old", "ancient = \"0.1\"");
+        member("tools/xtask", "xshell = \"0.2\"");
+        std::fs::create_dir_all(root.join("crates/notes")).unwrap();
+        let content = r#"
+[workspace]
+members = ["crates/*", "tools/xtask"]
+exclude = ["crates/old"]
+
+[workspace.dependencies]
+log = "0.4"
+"#;
+        let got = read_cargo_workspace(&root.join("Cargo.toml"), content);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(got.unwrap(), vec!["log", "clap", "serde", "xshell"]);
     }
 }
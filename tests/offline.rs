@@ -0,0 +1,320 @@
+//! Resolution against the registry responses in `tests/fixtures`, read
+//! through offline mode so no network is needed.
+
+use chrono::{TimeZone, Utc};
+use pkgtime::{
+    cargo_sparse_releases, fetch_pip_hashes, fetch_pip_requires, fetch_releases, find_version,
+    npm_latest_at, Fetcher, Manager, ResolveError, ResolveOptions,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn offline_fetcher() -> Fetcher {
+    let mut fetcher = Fetcher::new(Duration::from_secs(5)).unwrap();
+    fetcher.offline = Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+    fetcher
+}
+
+#[test]
+fn resolves_pip_fixture() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2020, 6, 10, 23, 59, 59).unwrap();
+
+    let v = find_version(
+        &fetcher,
+        Manager::Pip,
+        "requests",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "2.24.0rc1");
+
+    let opts = ResolveOptions {
+        stable_only: true,
+        ..Default::default()
+    };
+    let v = find_version(&fetcher, Manager::Pip, "requests", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "2.23.0");
+}
+
+#[test]
+fn records_pip_canonical_name() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2020, 1, 1, 23, 59, 59).unwrap();
+    let opts = ResolveOptions::default();
+
+    let v = find_version(&fetcher, Manager::Pip, "flask", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.1.1");
+    assert_eq!(
+        fetcher.canonical_name(Manager::Pip, "flask").as_deref(),
+        Some("Flask")
+    );
+
+    find_version(&fetcher, Manager::Pip, "requests", cutoff, &opts).unwrap();
+    assert_eq!(fetcher.canonical_name(Manager::Pip, "requests"), None);
+}
+
+#[test]
+fn resolves_cargo_fixture() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2021, 6, 1, 23, 59, 59).unwrap();
+    let v = find_version(
+        &fetcher,
+        Manager::Cargo,
+        "serde",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "1.0.126");
+
+    let opts = ResolveOptions {
+        include_yanked: true,
+        ..Default::default()
+    };
+    let v = find_version(&fetcher, Manager::Cargo, "serde", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.0.126-beta");
+}
+
+#[test]
+fn dates_apt_versions_by_first_snapshot() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2020, 8, 19, 23, 59, 59).unwrap();
+    let v = find_version(
+        &fetcher,
+        Manager::Apt,
+        "curl",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "7.72.0-1");
+    assert_eq!(
+        v.date,
+        Utc.with_ymd_and_hms(2020, 8, 19, 21, 5, 49).unwrap()
+    );
+}
+
+#[test]
+fn missing_fixture_is_an_unknown_package() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2021, 6, 1, 23, 59, 59).unwrap();
+    let err = find_version(
+        &fetcher,
+        Manager::Cargo,
+        "no-such-crate",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ResolveError::NotFound(_)));
+    assert_eq!(err.to_string(), "Crate not found on Crates.io");
+}
+
+#[test]
+fn npm_latest_follows_dist_tag() {
+    let fetcher = offline_fetcher();
+    let batch = ResolveOptions {
+        npm_latest: true,
+        ..Default::default()
+    };
+
+    // Unchanged since before the cutoff: today's `latest`, not the newer beta
+    let cutoff = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+    let v = npm_latest_at(&fetcher, "is-odd", cutoff, &batch)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "2.0.0");
+    let v = find_version(
+        &fetcher,
+        Manager::Npm,
+        "is-odd",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "3.0.0-beta.1");
+
+    // Modified since: the newest stable release before the cutoff
+    let cutoff = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+    let v = npm_latest_at(&fetcher, "is-odd", cutoff, &batch)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.0.0");
+}
+
+#[test]
+fn gem_platform_filters_builds() {
+    let mut fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2022, 2, 1, 0, 0, 0).unwrap();
+    let opts = ResolveOptions::default();
+
+    let v = find_version(&fetcher, Manager::Gem, "nokogiri", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.13.0");
+
+    fetcher.gem_platform = "x86_64-linux".to_string();
+    let v = find_version(&fetcher, Manager::Gem, "nokogiri", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.13.1");
+}
+
+#[test]
+fn github_releases_skip_drafts() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    let v = find_version(
+        &fetcher,
+        Manager::Github,
+        "BurntSushi/ripgrep",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "13.0.0");
+}
+
+#[test]
+fn pip_hashes_cover_every_file() {
+    let fetcher = offline_fetcher();
+    let hashes = fetch_pip_hashes(&fetcher, "requests", "2.22.0").unwrap();
+    assert_eq!(
+        hashes,
+        [
+            "11e007a8a2aa0323f5a921e9e6a2d7e4e67d9877e85773fba9ba6419025cbeb4",
+            "9cf5292fcd0f598c671cfc1e0d7d1a7f13bb8085e9a590f48c010551dc6c4b31",
+        ]
+    );
+}
+
+#[test]
+fn not_found_suggests_a_close_name() {
+    let fetcher = offline_fetcher();
+    let err = fetch_releases(&fetcher, Manager::Cargo, "serdd").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Package 'serdd' not found; did you mean 'serde'?"
+    );
+}
+
+#[test]
+fn pip_requires_skip_extras() {
+    let fetcher = offline_fetcher();
+    let requires = fetch_pip_requires(&fetcher, "requests", "2.22.0").unwrap();
+    let names: Vec<&str> = requires.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["chardet", "idna", "urllib3", "certifi"]);
+    assert_eq!(requires[1].1.as_deref(), Some("<2.9,>=2.5"));
+}
+
+#[test]
+fn composer_strips_v_prefix() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2021, 11, 30, 0, 0, 0).unwrap();
+    let v = find_version(
+        &fetcher,
+        Manager::Composer,
+        "symfony/console",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "5.4.0");
+}
+
+#[test]
+fn cargo_sparse_dates_versions_near_the_cutoff() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2021, 6, 1, 23, 59, 59).unwrap();
+    let releases = cargo_sparse_releases(&fetcher, "serde", cutoff).unwrap();
+    let versions: Vec<&str> = releases.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(versions, ["1.0.125", "1.0.126", "1.0.126-beta"]);
+    assert!(releases[2].yanked);
+
+    let opts = ResolveOptions {
+        cargo_sparse: true,
+        ..Default::default()
+    };
+    let v = find_version(&fetcher, Manager::Cargo, "serde", cutoff, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.version, "1.0.126");
+}
+
+#[test]
+fn all_dates_unreadable_is_not_an_empty_history() {
+    let fetcher = offline_fetcher();
+    let err = fetch_releases(&fetcher, Manager::Cargo, "undated-crate").unwrap_err();
+    assert!(matches!(err, ResolveError::Parse(_)));
+    assert_eq!(
+        err.to_string(),
+        "The registry lists 2 version(s) of 'undated-crate', but none with a date that could be read"
+    );
+}
+
+#[test]
+fn custom_date_formats_count_unreadable_dates_too() {
+    // CocoaPods writes "2019-05-13 18:41:05 UTC"; neither entry matches
+    let fetcher = offline_fetcher();
+    let err = fetch_releases(&fetcher, Manager::Pods, "UndatedPod").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The registry lists 2 version(s) of 'UndatedPod', but none with a date that could be read"
+    );
+}
+
+#[test]
+fn npm_latest_still_applies_max_age() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+    let opts = ResolveOptions {
+        npm_latest: true,
+        max_age_days: Some(30),
+        ..Default::default()
+    };
+    let err = find_version(&fetcher, Manager::Npm, "is-odd", cutoff, &opts).unwrap_err();
+    assert!(matches!(err, ResolveError::TooOld { .. }), "{}", err);
+
+    // A filter today's `latest` fails sends it down the ranking path
+    let opts = ResolveOptions {
+        npm_latest: true,
+        min_date: Some(cutoff),
+        ..Default::default()
+    };
+    let v = find_version(&fetcher, Manager::Npm, "is-odd", cutoff, &opts).unwrap();
+    assert!(v.is_none(), "{:?}", v.map(|v| v.version));
+}
+
+#[test]
+fn cpan_records_the_main_module() {
+    let fetcher = offline_fetcher();
+    let cutoff = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let v = find_version(
+        &fetcher,
+        Manager::Cpan,
+        "libwww-perl",
+        cutoff,
+        &ResolveOptions::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(v.version, "6.43");
+    assert_eq!(
+        fetcher.cpan_main_module("libwww-perl", "6.43").as_deref(),
+        Some("LWP")
+    );
+}